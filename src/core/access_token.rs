@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        client_auth::ClientAuth,
+        error::LogtoError,
+        fetch_token::{fetch_token_by_refresh_token, TokenByRefreshTokenParameters},
+        scope::Scopes,
+    },
+    utils::{decode_id_token::decode_id_token, verify_access_token::AccessTokenClaims},
+};
+
+/// Resource indicator Logto issues organization tokens for
+pub const ORGANIZATION_RESOURCE: &str = "urn:logto:resource:organizations";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub token: String,
+    pub scope: Scopes,
+    /// Unix timestamp in seconds
+    pub expires_at: u64,
+}
+
+impl AccessToken {
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+
+    pub fn expires_within(&self, leeway: Duration) -> bool {
+        self.expires_at <= now() + leeway.as_secs()
+    }
+}
+
+pub type AccessTokenMap = HashMap<String, AccessToken>;
+
+/// Builds the `AccessTokenMap` key, following the other Logto SDKs:
+/// `{sorted scopes}@{resource}#{organization_id}`
+pub fn build_access_token_key(
+    resource: Option<&str>,
+    organization_id: Option<&str>,
+    scopes: Option<&Scopes>,
+) -> String {
+    let mut scopes = scopes.cloned().unwrap_or_default();
+    scopes.sort();
+
+    let mut key = format!("{}@{}", scopes, resource.unwrap_or_default());
+
+    if let Some(organization_id) = organization_id {
+        key.push('#');
+        key.push_str(organization_id);
+    }
+
+    key
+}
+
+pub struct OrganizationTokenParameters<'a> {
+    pub token_endpoint: String,
+    pub client_id: &'a str,
+    pub refresh_token: &'a str,
+    pub organization_id: &'a str,
+    pub client_auth: &'a ClientAuth,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OrganizationToken {
+    pub access_token: AccessToken,
+    /// The rotated refresh token, `None` when the token was served from the map
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OrganizationTokenClaims {
+    pub organization_id: String,
+    pub sub: String,
+    pub scope: Scopes,
+    /// Resolved from the `organization_roles` claim of the ID token
+    pub roles: Vec<String>,
+    pub exp: u64,
+}
+
+/// Returns the access token for an organization from the map, or exchanges
+/// the refresh token for a new one and stores it.
+pub async fn get_organization_token<'a>(
+    client: &Client,
+    access_token_map: &mut AccessTokenMap,
+    parameters: OrganizationTokenParameters<'a>,
+) -> Result<OrganizationToken, LogtoError> {
+    let key = build_access_token_key(
+        Some(ORGANIZATION_RESOURCE),
+        Some(parameters.organization_id),
+        None,
+    );
+
+    if let Some(access_token) = access_token_map.get(&key) {
+        if !access_token.is_expired() {
+            return Ok(OrganizationToken {
+                access_token: access_token.clone(),
+                refresh_token: None,
+            });
+        }
+    }
+
+    let response = fetch_token_by_refresh_token(
+        client,
+        TokenByRefreshTokenParameters {
+            token_endpoint: parameters.token_endpoint,
+            client_id: parameters.client_id,
+            refresh_token: parameters.refresh_token,
+            resource: Some(ORGANIZATION_RESOURCE),
+            scopes: None,
+            organization_id: Some(parameters.organization_id),
+            client_auth: parameters.client_auth,
+            dpop: None,
+        },
+    )
+    .await?;
+
+    let access_token = AccessToken {
+        token: response.access_token,
+        scope: response.scope.parse().unwrap_or_default(),
+        expires_at: now() + response.expires_in.max(0) as u64,
+    };
+
+    access_token_map.insert(key, access_token.clone());
+
+    Ok(OrganizationToken {
+        access_token,
+        refresh_token: Some(response.refresh_token),
+    })
+}
+
+/// Returns the scopes granted in an organization, and the user's roles in it
+/// when an ID token issued with the `urn:logto:scope:organization_roles` scope
+/// is given.
+pub async fn get_organization_token_claims<'a>(
+    client: &Client,
+    access_token_map: &mut AccessTokenMap,
+    parameters: OrganizationTokenParameters<'a>,
+    id_token: Option<&str>,
+) -> Result<(OrganizationToken, OrganizationTokenClaims), Box<dyn Error>> {
+    let organization_id = parameters.organization_id.to_string();
+    let organization_token = get_organization_token(client, access_token_map, parameters).await?;
+
+    let key = DecodingKey::from_secret(&[]);
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.validate_aud = false;
+    validation.insecure_disable_signature_validation();
+
+    let claims =
+        decode::<AccessTokenClaims>(&organization_token.access_token.token, &key, &validation)?
+            .claims;
+
+    let roles = match id_token {
+        Some(id_token) => decode_id_token(id_token)?.roles_in_organization(&organization_id),
+        None => Vec::new(),
+    };
+
+    Ok((
+        organization_token,
+        OrganizationTokenClaims {
+            organization_id,
+            sub: claims.sub,
+            scope: claims.scope,
+            roles,
+            exp: claims.exp,
+        },
+    ))
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use mockito::Matcher;
+
+    use super::*;
+
+    #[test]
+    fn test_build_access_token_key() {
+        let scopes: Scopes = "write read".parse().unwrap();
+
+        assert_eq!(build_access_token_key(None, None, None), "@");
+        assert_eq!(
+            build_access_token_key(Some("https://api.example.com"), None, Some(&scopes)),
+            "read write@https://api.example.com"
+        );
+        assert_eq!(
+            build_access_token_key(Some(ORGANIZATION_RESOURCE), Some("org1"), None),
+            "@urn:logto:resource:organizations#org1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_organization_token_claims() {
+        let mut server = mockito::Server::new();
+
+        let claims = AccessTokenClaims {
+            iss: "https://logto.dev/oidc".to_string(),
+            sub: "user".to_string(),
+            aud: "urn:logto:organization:org1".to_string(),
+            exp: now() + 3600,
+            iat: now(),
+            jti: None,
+            client_id: Some("client_id_value".to_string()),
+            scope: "read:members invite:members".parse().unwrap(),
+            act: None,
+            cnf: None,
+        };
+        let key = EncodingKey::from_secret(b"secret");
+        let organization_token = encode(&Header::default(), &claims, &key).unwrap();
+
+        let id_token = encode(
+            &Header::default(),
+            &serde_json::json!({
+                "sub": "user",
+                "aud": "client_id_value",
+                "iss": "https://logto.dev/oidc",
+                "exp": now() + 3600,
+                "iat": now(),
+                "organization_roles": ["org1:admin", "org2:member"],
+            }),
+            &key,
+        )
+        .unwrap();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("refresh_token".into(), "refresh_token_value".into()),
+            Matcher::UrlEncoded("resource".into(), ORGANIZATION_RESOURCE.into()),
+            Matcher::UrlEncoded("organization_id".into(), "org1".into()),
+            Matcher::UrlEncoded("grant_type".into(), "refresh_token".into()),
+        ];
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{
+                    "access_token": "{}",
+                    "refresh_token": "new_refresh_token_value",
+                    "scope": "read:members invite:members",
+                    "expires_in": 3600
+                }}"#,
+                organization_token
+            ))
+            .expect(1)
+            .create();
+
+        let client = reqwest::Client::new();
+        let mut access_token_map = AccessTokenMap::new();
+        let endpoint = format!("{}/oidc/token", server.url());
+
+        let parameters = || OrganizationTokenParameters {
+            token_endpoint: endpoint.clone(),
+            client_id: "client_id_value",
+            refresh_token: "refresh_token_value",
+            organization_id: "org1",
+            client_auth: &ClientAuth::None,
+        };
+
+        let (token, claims) = get_organization_token_claims(
+            &client,
+            &mut access_token_map,
+            parameters(),
+            Some(&id_token),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            token.refresh_token,
+            Some("new_refresh_token_value".to_string())
+        );
+        assert_eq!(claims.organization_id, "org1");
+        assert_eq!(claims.scope.to_string(), "read:members invite:members");
+        assert_eq!(claims.roles, vec!["admin"]);
+
+        let cached = get_organization_token(&client, &mut access_token_map, parameters())
+            .await
+            .unwrap();
+
+        assert_eq!(cached.access_token, token.access_token);
+        assert_eq!(cached.refresh_token, None);
+        mock.assert();
+    }
+}
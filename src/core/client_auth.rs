@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use josekit::{
+    jwk::{
+        alg::{ec::EcKeyPair, rsa::RsaKeyPair},
+        Jwk, KeyPair,
+    },
+    jws::{
+        alg::{
+            ecdsa::EcdsaJwsAlgorithm::{Es256, Es384, Es512},
+            hmac::HmacJwsAlgorithm::Hs256,
+            rsassa::RsassaJwsAlgorithm::{Rs256, Rs384, Rs512},
+            rsassa_pss::RsassaPssJwsAlgorithm::{Ps256, Ps384, Ps512},
+        },
+        JwsHeader, JwsSigner,
+    },
+    jwt::{self, JwtPayload},
+    JoseError,
+};
+use reqwest::RequestBuilder;
+use url::form_urlencoded;
+
+use crate::utils::generators::generate_random_string;
+
+const CLIENT_ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+const CLIENT_ASSERTION_LIFETIME: Duration = Duration::from_secs(60);
+
+/// How the client authenticates to the token and revocation endpoints,
+/// see https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ClientAuth {
+    /// Public clients (SPA, native) only send their `client_id`
+    #[default]
+    None,
+    ClientSecretBasic(String),
+    ClientSecretPost(String),
+    /// RFC 7523 client assertion signed with the client secret (HS256)
+    ClientSecretJwt(String),
+    /// RFC 7523 client assertion signed with the client's private key
+    PrivateKeyJwt(ClientAssertionKey),
+}
+
+/// Private key used to sign `private_key_jwt` client assertions. RSA keys
+/// sign with RS256, EC keys with ES256, ES384 or ES512 depending on the curve,
+/// unless the JWK sets `alg`. Only the RS, PS and ES algorithms are supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientAssertionKey {
+    jwk: Jwk,
+}
+
+impl ClientAssertionKey {
+    pub fn from_jwk(jwk: Jwk) -> Self {
+        Self { jwk }
+    }
+
+    /// Reads a PKCS#8, PKCS#1 (RSA) or SEC1 (EC) PEM private key
+    pub fn from_pem(pem: impl AsRef<[u8]>, key_id: Option<&str>) -> Result<Self, JoseError> {
+        let mut jwk = match RsaKeyPair::from_pem(pem.as_ref()) {
+            Ok(key_pair) => key_pair.to_jwk_private_key(),
+            Err(_) => EcKeyPair::from_pem(pem.as_ref(), None)?.to_jwk_private_key(),
+        };
+
+        if let Some(key_id) = key_id {
+            jwk.set_key_id(key_id);
+        }
+
+        Ok(Self { jwk })
+    }
+
+    fn signer(&self) -> Result<Box<dyn JwsSigner>, JoseError> {
+        let algorithm = match (self.jwk.algorithm(), self.jwk.curve()) {
+            (Some(algorithm), _) => algorithm,
+            (None, Some("P-256")) => "ES256",
+            (None, Some("P-384")) => "ES384",
+            (None, Some("P-521")) => "ES512",
+            (None, _) if self.jwk.key_type() == "RSA" => "RS256",
+            (None, _) => self.jwk.key_type(),
+        };
+
+        let signer: Box<dyn JwsSigner> = match algorithm {
+            "RS256" => Box::new(Rs256.signer_from_jwk(&self.jwk)?),
+            "RS384" => Box::new(Rs384.signer_from_jwk(&self.jwk)?),
+            "RS512" => Box::new(Rs512.signer_from_jwk(&self.jwk)?),
+            "PS256" => Box::new(Ps256.signer_from_jwk(&self.jwk)?),
+            "PS384" => Box::new(Ps384.signer_from_jwk(&self.jwk)?),
+            "PS512" => Box::new(Ps512.signer_from_jwk(&self.jwk)?),
+            "ES256" => Box::new(Es256.signer_from_jwk(&self.jwk)?),
+            "ES384" => Box::new(Es384.signer_from_jwk(&self.jwk)?),
+            "ES512" => Box::new(Es512.signer_from_jwk(&self.jwk)?),
+            algorithm => {
+                return Err(JoseError::UnsupportedSignatureAlgorithm(anyhow::anyhow!(
+                    "{} can't sign client assertions",
+                    algorithm
+                )))
+            }
+        };
+
+        Ok(signer)
+    }
+}
+
+impl ClientAuth {
+    /// Authenticates the request as `client_id` and sets `params` as its form
+    /// body. Client assertions use `token_endpoint` as their audience, whichever
+    /// endpoint is called.
+    pub(crate) fn apply<'a>(
+        &'a self,
+        request: RequestBuilder,
+        client_id: &'a str,
+        token_endpoint: &str,
+        mut params: HashMap<&'a str, &'a str>,
+    ) -> Result<RequestBuilder, JoseError> {
+        let client_assertion = match self {
+            Self::None => {
+                params.insert("client_id", client_id);
+                return Ok(request.form(&params));
+            }
+            Self::ClientSecretBasic(client_secret) => {
+                return Ok(request
+                    .basic_auth(form_encode(client_id), Some(form_encode(client_secret)))
+                    .form(&params));
+            }
+            Self::ClientSecretPost(client_secret) => {
+                params.insert("client_id", client_id);
+                params.insert("client_secret", client_secret);
+                return Ok(request.form(&params));
+            }
+            Self::ClientSecretJwt(client_secret) => sign_client_assertion(
+                &Hs256.signer_from_bytes(client_secret)?,
+                None,
+                client_id,
+                token_endpoint,
+            )?,
+            Self::PrivateKeyJwt(key) => {
+                sign_client_assertion(&*key.signer()?, key.jwk.key_id(), client_id, token_endpoint)?
+            }
+        };
+
+        // Shortens the lifetime of the borrowed values so the assertion fits in
+        let mut params: HashMap<&str, &str> = params;
+        params.insert("client_id", client_id);
+        params.insert("client_assertion_type", CLIENT_ASSERTION_TYPE);
+        params.insert("client_assertion", &client_assertion);
+
+        Ok(request.form(&params))
+    }
+}
+
+// https://datatracker.ietf.org/doc/html/rfc7523#section-3
+fn sign_client_assertion(
+    signer: &dyn JwsSigner,
+    key_id: Option<&str>,
+    client_id: &str,
+    audience: &str,
+) -> Result<String, JoseError> {
+    let mut header = JwsHeader::new();
+    header.set_token_type("JWT");
+    if let Some(key_id) = key_id {
+        header.set_key_id(key_id);
+    }
+
+    let now = SystemTime::now();
+
+    let mut payload = JwtPayload::new();
+    payload.set_issuer(client_id);
+    payload.set_subject(client_id);
+    payload.set_audience(vec![audience]);
+    payload.set_jwt_id(generate_random_string());
+    payload.set_issued_at(&now);
+    payload.set_expires_at(&(now + CLIENT_ASSERTION_LIFETIME));
+
+    jwt::encode_with_signer(&payload, &header, signer)
+}
+
+// https://datatracker.ietf.org/doc/html/rfc6749#section-2.3.1
+fn form_encode(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn client_secret_basic() {
+        let mut server = mockito::Server::new();
+
+        // base64("client%3Aid:s%C3%A9cret+value")
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .match_header(
+                "authorization",
+                "Basic Y2xpZW50JTNBaWQ6cyVDMyVBOWNyZXQrdmFsdWU=",
+            )
+            .match_body(Matcher::Exact("grant_type=refresh_token".into()))
+            .with_status(200)
+            .create();
+
+        let client = reqwest::Client::new();
+        let auth = ClientAuth::ClientSecretBasic("sécret value".to_string());
+
+        let mut params = HashMap::new();
+        params.insert("grant_type", "refresh_token");
+
+        auth.apply(
+            client.post(format!("{}/oidc/token", server.url())),
+            "client:id",
+            "https://logto.dev/oidc/token",
+            params,
+        )
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn client_secret_post() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .match_header("authorization", Matcher::Missing)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "refresh_token".into()),
+                Matcher::UrlEncoded("client_id".into(), "client_id_value".into()),
+                Matcher::UrlEncoded("client_secret".into(), "client_secret_value".into()),
+            ]))
+            .with_status(200)
+            .create();
+
+        let client = reqwest::Client::new();
+        let auth = ClientAuth::ClientSecretPost("client_secret_value".to_string());
+
+        let mut params = HashMap::new();
+        params.insert("grant_type", "refresh_token");
+
+        auth.apply(
+            client.post(format!("{}/oidc/token", server.url())),
+            "client_id_value",
+            "https://logto.dev/oidc/token",
+            params,
+        )
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+        mock.assert();
+    }
+
+    fn client_assertion_claims(auth: &ClientAuth) -> josekit::Map<String, josekit::Value> {
+        let mut params = HashMap::new();
+        params.insert("grant_type", "refresh_token");
+
+        // The audience stays the token endpoint whichever endpoint is called
+        let request = auth
+            .apply(
+                reqwest::Client::new().post("https://logto.dev/oidc/token/revocation"),
+                "client_id_value",
+                "https://logto.dev/oidc/token",
+                params,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let body = std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        let form: HashMap<String, String> = form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+
+        assert_eq!(form["client_assertion_type"], CLIENT_ASSERTION_TYPE);
+        assert_eq!(form["client_id"], "client_id_value");
+
+        let (_, payload) = form["client_assertion"].split_once('.').unwrap();
+        let (payload, _) = payload.split_once('.').unwrap();
+        let payload =
+            base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, payload)
+                .unwrap();
+
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[test]
+    fn client_secret_jwt() {
+        let claims = client_assertion_claims(&ClientAuth::ClientSecretJwt(
+            "a_client_secret_of_at_least_32_bytes".into(),
+        ));
+
+        assert_eq!(claims["iss"], "client_id_value");
+        assert_eq!(claims["sub"], "client_id_value");
+        assert_eq!(claims["aud"], "https://logto.dev/oidc/token");
+        assert_eq!(
+            claims["exp"].as_u64().unwrap() - claims["iat"].as_u64().unwrap(),
+            CLIENT_ASSERTION_LIFETIME.as_secs()
+        );
+    }
+
+    #[test]
+    fn private_key_jwt_has_unique_jti() {
+        let key_pair = Es256.generate_key_pair().unwrap();
+        let key =
+            ClientAssertionKey::from_pem(key_pair.to_pem_private_key(), Some("key_id")).unwrap();
+        let auth = ClientAuth::PrivateKeyJwt(key);
+
+        let first_claims = client_assertion_claims(&auth);
+        let second_claims = client_assertion_claims(&auth);
+
+        assert_eq!(first_claims["aud"], "https://logto.dev/oidc/token");
+        assert_ne!(first_claims["jti"], second_claims["jti"]);
+    }
+
+    #[test]
+    fn signer_follows_jwk_alg() {
+        let mut jwk = Rs256.generate_key_pair(2048).unwrap().to_jwk_private_key();
+        jwk.set_algorithm("PS384");
+
+        let signer = ClientAssertionKey::from_jwk(jwk).signer().unwrap();
+
+        assert_eq!(signer.algorithm().name(), "PS384");
+    }
+
+    #[test]
+    fn signer_rejects_unsupported_alg() {
+        let jwk = josekit::jws::EdDSA
+            .generate_key_pair(josekit::jwk::alg::ed::EdCurve::Ed25519)
+            .unwrap()
+            .to_jwk_private_key();
+
+        assert!(ClientAssertionKey::from_jwk(jwk).signer().is_err());
+
+        let mut jwk = Rs256.generate_key_pair(2048).unwrap().to_jwk_private_key();
+        jwk.set_algorithm("EdDSA");
+
+        assert!(ClientAssertionKey::from_jwk(jwk).signer().is_err());
+    }
+}
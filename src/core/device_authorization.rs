@@ -0,0 +1,297 @@
+use std::{collections::HashMap, time::Duration};
+
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::time::{sleep, Instant};
+
+use crate::core::{
+    client_auth::ClientAuth,
+    error::{parse_response, LogtoError, OAuthError},
+    fetch_token::CodeTokenResponse,
+    scope::Scopes,
+};
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_INTERVAL: u64 = 5;
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+pub struct DeviceAuthorizationParameters<'a> {
+    pub device_authorization_endpoint: &'a str,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub scopes: Option<Scopes>,
+    pub resource: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
+}
+
+/// The codes the user enters at `verification_uri`, or opens directly
+/// through `verification_uri_complete`
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL
+}
+
+pub struct TokenByDeviceCodeParameters<'a> {
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub device_code: &'a str,
+    /// Time to wait between polls, `slow_down` responses increase it
+    pub interval: Duration,
+    /// Polling stops with `expired_token` once the device code expired
+    pub expires_in: Duration,
+    pub client_auth: &'a ClientAuth,
+}
+
+impl<'a> TokenByDeviceCodeParameters<'a> {
+    pub fn from_response(
+        token_endpoint: &'a str,
+        client_id: &'a str,
+        client_auth: &'a ClientAuth,
+        response: &'a DeviceAuthorizationResponse,
+    ) -> Self {
+        Self {
+            token_endpoint,
+            client_id,
+            device_code: &response.device_code,
+            interval: Duration::from_secs(response.interval),
+            expires_in: Duration::from_secs(response.expires_in),
+            client_auth,
+        }
+    }
+}
+
+/// Starts the device authorization grant,
+/// see https://datatracker.ietf.org/doc/html/rfc8628#section-3.1
+pub async fn fetch_device_authorization<'a>(
+    client: &Client,
+    parameters: DeviceAuthorizationParameters<'a>,
+) -> Result<DeviceAuthorizationResponse, LogtoError> {
+    let mut params = HashMap::new();
+
+    let scope = parameters.scopes.unwrap_or_default().to_string();
+
+    if !scope.is_empty() {
+        params.insert("scope", scope.as_str());
+    }
+
+    if let Some(resource) = parameters.resource {
+        params.insert("resource", resource);
+    }
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(parameters.device_authorization_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params,
+        )?
+        .send()
+        .await?;
+
+    parse_response(response).await
+}
+
+/// Polls the token endpoint until the user approved or denied the device,
+/// see https://datatracker.ietf.org/doc/html/rfc8628#section-3.4
+pub async fn fetch_token_by_device_code<'a>(
+    client: &Client,
+    parameters: TokenByDeviceCodeParameters<'a>,
+) -> Result<CodeTokenResponse, LogtoError> {
+    let deadline = Instant::now() + parameters.expires_in;
+    let mut interval = parameters.interval;
+
+    loop {
+        sleep(interval).await;
+
+        if Instant::now() >= deadline {
+            return Err(LogtoError::OAuth(OAuthError {
+                error: "expired_token".to_string(),
+                error_description: Some("device code expired before approval".to_string()),
+                error_uri: None,
+            }));
+        }
+
+        let mut params = HashMap::new();
+        params.insert("grant_type", DEVICE_CODE_GRANT_TYPE);
+        params.insert("device_code", parameters.device_code);
+
+        let response = parameters
+            .client_auth
+            .apply(
+                client.post(parameters.token_endpoint),
+                parameters.client_id,
+                parameters.token_endpoint,
+                params,
+            )?
+            .send()
+            .await?;
+
+        match parse_response(response).await {
+            Err(e) if e.oauth_error() == Some("authorization_pending") => continue,
+            Err(e) if e.oauth_error() == Some("slow_down") => interval += SLOW_DOWN_INCREMENT,
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_device_authorization() {
+        let mut server = mockito::Server::new();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("client_id".into(), "client_id_value".into()),
+            Matcher::UrlEncoded("scope".into(), "openid offline_access".into()),
+        ];
+
+        server
+            .mock("POST", "/oidc/device/auth")
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "device_code": "device_code_value",
+                    "user_code": "WDJB-MJHT",
+                    "verification_uri": "https://logto.dev/oidc/device",
+                    "verification_uri_complete": "https://logto.dev/oidc/device?user_code=WDJB-MJHT",
+                    "expires_in": 600
+                }"#,
+            )
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/device/auth", server.url());
+
+        let response = fetch_device_authorization(
+            &client,
+            DeviceAuthorizationParameters {
+                device_authorization_endpoint: &endpoint,
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_id: "client_id_value",
+                scopes: Some("openid offline_access".parse().unwrap()),
+                resource: None,
+                client_auth: &ClientAuth::None,
+            },
+        )
+        .await;
+
+        match response {
+            Ok(r) => assert_eq!(
+                r,
+                DeviceAuthorizationResponse {
+                    device_code: "device_code_value".to_string(),
+                    user_code: "WDJB-MJHT".to_string(),
+                    verification_uri: "https://logto.dev/oidc/device".to_string(),
+                    verification_uri_complete: Some(
+                        "https://logto.dev/oidc/device?user_code=WDJB-MJHT".to_string()
+                    ),
+                    expires_in: 600,
+                    interval: 5,
+                }
+            ),
+            Err(e) => panic!("Error in fetch_device_authorization: {}", e),
+        }
+    }
+
+    fn mock_token_error(server: &mut mockito::Server, error: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/oidc/token")
+            .match_body(Matcher::UrlEncoded(
+                "grant_type".into(),
+                DEVICE_CODE_GRANT_TYPE.into(),
+            ))
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"error": "{}"}}"#, error))
+            .expect(1)
+            .create()
+    }
+
+    fn parameters<'a>(endpoint: &'a str) -> TokenByDeviceCodeParameters<'a> {
+        TokenByDeviceCodeParameters {
+            token_endpoint: endpoint,
+            client_id: "client_id_value",
+            device_code: "device_code_value",
+            interval: Duration::from_millis(10),
+            expires_in: Duration::from_secs(10),
+            client_auth: &ClientAuth::None,
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_until_authorized() {
+        let mut server = mockito::Server::new();
+
+        let pending = mock_token_error(&mut server, "authorization_pending");
+        let success = server
+            .mock("POST", "/oidc/token")
+            .match_body(Matcher::UrlEncoded(
+                "device_code".into(),
+                "device_code_value".into(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "access_token": "access_token_value",
+                    "refresh_token": "refresh_token_value",
+                    "id_token": "id_token_value",
+                    "scope": "openid offline_access",
+                    "expires_in": 3600
+                }"#,
+            )
+            .expect(1)
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token", server.url());
+
+        let response = fetch_token_by_device_code(&client, parameters(&endpoint)).await;
+
+        match response {
+            Ok(r) => assert_eq!(r.access_token, "access_token_value"),
+            Err(e) => panic!("Error in fetch_token_by_device_code: {}", e),
+        }
+        pending.assert();
+        success.assert();
+    }
+
+    // The paused clock skips the `slow_down` backoff instead of sleeping it
+    #[tokio::test(start_paused = true)]
+    async fn stops_when_access_denied() {
+        let mut server = mockito::Server::new();
+
+        let slow_down = mock_token_error(&mut server, "slow_down");
+        let denied = mock_token_error(&mut server, "access_denied");
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token", server.url());
+
+        let response = fetch_token_by_device_code(&client, parameters(&endpoint)).await;
+
+        match response {
+            Ok(_) => panic!("Expected error but got ok"),
+            Err(e) => assert_eq!(e.oauth_error(), Some("access_denied")),
+        }
+        slow_down.assert();
+        denied.assert();
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use base64::{engine::general_purpose, Engine as _};
+use josekit::{
+    jwk::alg::ec::EcKeyPair,
+    jws::{alg::ecdsa::EcdsaJwsAlgorithm::Es256, JwsHeader},
+    jwt::{self, JwtPayload},
+    JoseError,
+};
+use reqwest::{RequestBuilder, Response, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    core::error::{parse_response, LogtoError},
+    utils::generators::generate_random_string,
+};
+
+pub const DPOP_HEADER: &str = "DPoP";
+pub const DPOP_NONCE_HEADER: &str = "DPoP-Nonce";
+pub const DPOP_PROOF_TYPE: &str = "dpop+jwt";
+
+/// Claims of a DPoP proof,
+/// see https://datatracker.ietf.org/doc/html/rfc9449#section-4.2
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct DpopProofClaims {
+    pub jti: String,
+    pub htm: String,
+    pub htu: String,
+    pub iat: u64,
+    /// Hash of the access token, set on resource server requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ath: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+/// EC P-256 key pair binding tokens to this client. Persist it across runs,
+/// tokens bound to a lost key can't be used anymore.
+pub struct DpopKey {
+    key_pair: EcKeyPair,
+    /// Last nonce the authorization server handed out
+    nonce: Mutex<Option<String>>,
+}
+
+impl DpopKey {
+    pub fn generate() -> Result<Self, JoseError> {
+        Ok(Self::new(Es256.generate_key_pair()?))
+    }
+
+    pub fn from_pem(pem: impl AsRef<[u8]>) -> Result<Self, JoseError> {
+        Ok(Self::new(Es256.key_pair_from_pem(pem)?))
+    }
+
+    pub fn to_pem(&self) -> Vec<u8> {
+        self.key_pair.to_pem_private_key()
+    }
+
+    /// Reads the PEM key at `path`, or generates one and writes it there,
+    /// readable by the owner only on unix
+    pub fn load_or_generate(path: impl AsRef<Path>) -> Result<Self, LogtoError> {
+        match fs::read(path.as_ref()) {
+            Ok(pem) => Ok(Self::from_pem(pem)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let key = Self::generate()?;
+                write_private_file(path.as_ref(), &key.to_pem())?;
+                Ok(key)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn new(key_pair: EcKeyPair) -> Self {
+        Self {
+            key_pair,
+            nonce: Mutex::new(None),
+        }
+    }
+
+    /// Signs a proof for a `method` request to `uri`. Pass the access token
+    /// for resource server requests, and the server's `DPoP-Nonce` if it
+    /// asked for one.
+    pub fn proof(
+        &self,
+        method: &str,
+        uri: &str,
+        access_token: Option<&str>,
+        nonce: Option<&str>,
+    ) -> Result<String, JoseError> {
+        let mut header = JwsHeader::new();
+        header.set_token_type(DPOP_PROOF_TYPE);
+        header.set_jwk(self.key_pair.to_jwk_public_key());
+
+        let mut payload = JwtPayload::new();
+        payload.set_jwt_id(generate_random_string());
+        payload.set_issued_at(&SystemTime::now());
+        payload.set_claim("htm", Some(Value::from(method)))?;
+        payload.set_claim("htu", Some(Value::from(htu(uri))))?;
+
+        if let Some(access_token) = access_token {
+            payload.set_claim("ath", Some(Value::from(access_token_hash(access_token))))?;
+        }
+
+        if let Some(nonce) = nonce {
+            payload.set_claim("nonce", Some(Value::from(nonce)))?;
+        }
+
+        let signer = Es256.signer_from_jwk(&self.key_pair.to_jwk_private_key())?;
+
+        jwt::encode_with_signer(&payload, &header, &signer)
+    }
+
+    fn store_nonce(&self, response: &Response) {
+        if let Some(nonce) = response
+            .headers()
+            .get(DPOP_NONCE_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.nonce.lock().expect("DPoP nonce lock poisoned") = Some(nonce.to_string());
+        }
+    }
+}
+
+// Writes to a temporary file first so a crash never leaves a partial key behind
+fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(format!(".{}.tmp", generate_random_string()));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let written = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    });
+
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    written
+}
+
+/// Sends a token request, with a DPoP proof when `dpop` is set. Retries once
+/// with the server's nonce when it answers `use_dpop_nonce`,
+/// see https://datatracker.ietf.org/doc/html/rfc9449#section-8
+pub(crate) async fn send_token_request<T: DeserializeOwned>(
+    dpop: Option<&DpopKey>,
+    endpoint: &str,
+    request: impl Fn() -> Result<RequestBuilder, JoseError>,
+) -> Result<T, LogtoError> {
+    let Some(dpop) = dpop else {
+        return parse_response(request()?.send().await?).await;
+    };
+
+    let mut retried = false;
+
+    loop {
+        let nonce = dpop.nonce.lock().expect("DPoP nonce lock poisoned").clone();
+        let proof = dpop.proof("POST", endpoint, None, nonce.as_deref())?;
+
+        let response = request()?.header(DPOP_HEADER, proof).send().await?;
+        dpop.store_nonce(&response);
+
+        match parse_response(response).await {
+            Err(e) if e.oauth_error() == Some("use_dpop_nonce") && !retried => retried = true,
+            result => return result,
+        }
+    }
+}
+
+/// The `ath` claim, base64url of the access token's SHA-256
+pub(crate) fn access_token_hash(access_token: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
+
+/// The `htu` claim is the request URI without query and fragment
+pub(crate) fn htu(uri: &str) -> String {
+    match Url::parse(uri) {
+        Ok(mut url) => {
+            url.set_query(None);
+            url.set_fragment(None);
+            url.to_string()
+        }
+        Err(_) => uri.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    fn decode_part(part: &str) -> serde_json::Map<String, Value> {
+        serde_json::from_slice(&general_purpose::URL_SAFE_NO_PAD.decode(part).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn proof_claims() {
+        let key = DpopKey::generate().unwrap();
+        let proof = key
+            .proof(
+                "GET",
+                "https://api.example.com/orders?page=2#top",
+                Some("access_token_value"),
+                Some("nonce_value"),
+            )
+            .unwrap();
+
+        let parts: Vec<&str> = proof.split('.').collect();
+        let header = decode_part(parts[0]);
+        let claims = decode_part(parts[1]);
+
+        assert_eq!(header["typ"], DPOP_PROOF_TYPE);
+        assert_eq!(header["alg"], "ES256");
+        assert!(header["jwk"].get("d").is_none());
+        assert_eq!(claims["htm"], "GET");
+        assert_eq!(claims["htu"], "https://api.example.com/orders");
+        assert_eq!(claims["ath"], access_token_hash("access_token_value"));
+        assert_eq!(claims["nonce"], "nonce_value");
+    }
+
+    #[test]
+    fn persists_key() {
+        let path = std::env::temp_dir().join(format!("dpop-{}.pem", generate_random_string()));
+
+        let generated = DpopKey::load_or_generate(&path).unwrap();
+        let loaded = DpopKey::load_or_generate(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(generated.to_pem(), loaded.to_pem());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("dpop-{}.pem", generate_random_string()));
+
+        DpopKey::load_or_generate(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[tokio::test]
+    async fn retries_with_server_nonce() {
+        let mut server = mockito::Server::new();
+
+        let nonce_required = server
+            .mock("POST", "/oidc/token")
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_header(DPOP_NONCE_HEADER, "nonce_value")
+            .with_body(r#"{"error": "use_dpop_nonce"}"#)
+            .expect(1)
+            .create();
+        let success = server
+            .mock("POST", "/oidc/token")
+            .match_header(DPOP_HEADER, Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ok": true}"#)
+            .expect(1)
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token", server.url());
+        let key = DpopKey::generate().unwrap();
+
+        let response: Value =
+            send_token_request(Some(&key), &endpoint, || Ok(client.post(&endpoint)))
+                .await
+                .unwrap();
+
+        assert_eq!(response["ok"], true);
+        assert_eq!(key.nonce.lock().unwrap().as_deref(), Some("nonce_value"));
+        nonce_required.assert();
+        success.assert();
+    }
+}
@@ -0,0 +1,164 @@
+use std::{error::Error, fmt};
+
+use josekit::JoseError;
+use reqwest::{Response, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::utils::verify_and_parse_code_from_callback_uri::AuthorizationError;
+
+/// Error response of the token, revocation and other OAuth endpoints,
+/// see https://datatracker.ietf.org/doc/html/rfc6749#section-5.2
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct OAuthError {
+    pub error: String,
+    pub error_description: Option<String>,
+    pub error_uri: Option<String>,
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.error)?;
+
+        if let Some(description) = &self.error_description {
+            write!(f, " ({})", description)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error response of the Management API
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ManagementError {
+    #[serde(skip)]
+    pub status: u16,
+    /// E.g. `user.username_already_in_use`
+    pub code: String,
+    pub message: String,
+}
+
+impl fmt::Display for ManagementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.status, self.code, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum LogtoError {
+    Request(reqwest::Error),
+    ClientAssertion(JoseError),
+    OAuth(OAuthError),
+    Management(ManagementError),
+    Io(std::io::Error),
+    /// The callback carried an `error` from the authorization server
+    Authorization(AuthorizationError),
+    /// The callback failed validation, e.g. the state didn't match
+    InvalidCallback(String),
+    InvalidUrl(String),
+    /// A success status other than the one the endpoint answers with
+    UnexpectedStatus(StatusCode),
+    TimedOut,
+    Cancelled,
+}
+
+impl LogtoError {
+    /// The OAuth `error` code, if the server answered with one
+    pub fn oauth_error(&self) -> Option<&str> {
+        match self {
+            Self::OAuth(e) => Some(&e.error),
+            _ => None,
+        }
+    }
+
+    /// The Management API error `code`, if the API answered with one
+    pub fn management_error(&self) -> Option<&str> {
+        match self {
+            Self::Management(e) => Some(&e.code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LogtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "request failed: {}", e),
+            Self::ClientAssertion(e) => write!(f, "couldn't sign client assertion: {}", e),
+            Self::OAuth(e) => write!(f, "authorization server error: {}", e),
+            Self::Management(e) => write!(f, "management api error: {}", e),
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Authorization(e) => write!(f, "{}", e),
+            Self::InvalidCallback(e) => write!(f, "invalid callback: {}", e),
+            Self::InvalidUrl(e) => write!(f, "invalid url: {}", e),
+            Self::UnexpectedStatus(status) => write!(f, "unexpected status: {}", status),
+            Self::TimedOut => f.write_str("timed out"),
+            Self::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
+impl Error for LogtoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Request(e) => Some(e),
+            Self::ClientAssertion(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Authorization(e) => Some(e),
+            Self::OAuth(_)
+            | Self::Management(_)
+            | Self::InvalidCallback(_)
+            | Self::InvalidUrl(_)
+            | Self::UnexpectedStatus(_)
+            | Self::TimedOut
+            | Self::Cancelled => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LogtoError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+impl From<std::io::Error> for LogtoError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Keeps `AuthorizationError`s typed, other callback errors become `InvalidCallback`
+impl From<Box<dyn Error>> for LogtoError {
+    fn from(e: Box<dyn Error>) -> Self {
+        match e.downcast::<AuthorizationError>() {
+            Ok(e) => Self::Authorization(*e),
+            Err(e) => Self::InvalidCallback(e.to_string()),
+        }
+    }
+}
+
+impl From<JoseError> for LogtoError {
+    fn from(e: JoseError) -> Self {
+        Self::ClientAssertion(e)
+    }
+}
+
+/// Decodes a successful response as `T`, and an error response as an
+/// `OAuthError` when it has one
+pub(crate) async fn parse_response<T: DeserializeOwned>(
+    response: Response,
+) -> Result<T, LogtoError> {
+    Ok(check_response(response).await?.json::<T>().await?)
+}
+
+/// Returns a successful response as is, and decodes an error response as an
+/// `OAuthError` when it has one
+pub(crate) async fn check_response(response: Response) -> Result<Response, LogtoError> {
+    match response.error_for_status_ref() {
+        Ok(_) => Ok(response),
+        Err(e) => match response.json::<OAuthError>().await {
+            Ok(oauth_error) => Err(LogtoError::OAuth(oauth_error)),
+            Err(_) => Err(e.into()),
+        },
+    }
+}
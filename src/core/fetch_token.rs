@@ -1,51 +1,153 @@
 use std::collections::HashMap;
 
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use reqwest::Client;
 use serde::Deserialize;
 
-struct TokenByAuthorizationCodeParameters<'a> {
-    token_endpoint: &'a str,
-    code: &'a str,
-    code_verifier: &'a str,
-    client_id: &'a str,
-    redirect_uri: &'a str,
-    resource: Option<&'a str>,
+use crate::{
+    core::{
+        client_auth::ClientAuth,
+        dpop::{send_token_request, DpopKey},
+        error::{parse_response, LogtoError},
+        scope::Scopes,
+    },
+    utils::verify_access_token::{AccessTokenClaims, ActorClaim},
+};
+
+const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+pub struct TokenByAuthorizationCodeParameters<'a> {
+    pub token_endpoint: &'a str,
+    pub code: &'a str,
+    pub code_verifier: &'a str,
+    pub client_id: &'a str,
+    pub redirect_uri: &'a str,
+    pub resource: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
+    /// Binds the issued tokens to this key
+    pub dpop: Option<&'a DpopKey>,
 }
 
-struct TokenByRefreshTokenParameters {
-    token_endpoint: String,
-    client_id: &'static str,
-    refresh_token: &'static str,
-    resource: Option<&'static str>,
-    scopes: Option<Vec<&'static str>>,
+pub struct TokenByRefreshTokenParameters<'a> {
+    pub token_endpoint: String,
+    pub client_id: &'a str,
+    pub refresh_token: &'a str,
+    pub resource: Option<&'a str>,
+    pub scopes: Option<Scopes>,
+    pub organization_id: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
+    /// Must be the key the refresh token is bound to, if any
+    pub dpop: Option<&'a DpopKey>,
+}
+
+pub struct TokenByClientCredentialsParameters<'a> {
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub resource: Option<&'a str>,
+    pub scopes: Option<Scopes>,
+    pub organization_id: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
+}
+
+/// Token type identifiers for token exchange,
+/// see https://datatracker.ietf.org/doc/html/rfc8693#section-3
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenType {
+    /// Also the type of Logto subject tokens created through the Management API
+    AccessToken,
+    RefreshToken,
+    IdToken,
+    Jwt,
+}
+
+impl TokenType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AccessToken => "urn:ietf:params:oauth:token-type:access_token",
+            Self::RefreshToken => "urn:ietf:params:oauth:token-type:refresh_token",
+            Self::IdToken => "urn:ietf:params:oauth:token-type:id_token",
+            Self::Jwt => "urn:ietf:params:oauth:token-type:jwt",
+        }
+    }
+}
+
+pub struct ActorToken<'a> {
+    pub token: &'a str,
+    pub token_type: TokenType,
+}
+
+pub struct TokenExchangeParameters<'a> {
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub subject_token: &'a str,
+    pub subject_token_type: TokenType,
+    /// The party acting on behalf of the subject, ends up in the `act` claim
+    pub actor_token: Option<ActorToken<'a>>,
+    pub resource: Option<&'a str>,
+    pub scopes: Option<Scopes>,
+    pub organization_id: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
 }
 
 // TODO: refactor this to a generic or something composed?
 
 #[derive(Debug, PartialEq, Deserialize)]
-struct CodeTokenResponse {
-    access_token: String,
-    refresh_token: Option<String>,
-    id_token: String,
-    scope: String,
-    expires_in: i16,
+pub struct CodeTokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub id_token: String,
+    pub scope: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct RefreshTokenTokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub id_token: Option<String>,
+    pub scope: String,
+    pub expires_in: i64,
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
-struct RefreshTokenTokenResponse {
-    access_token: String,
-    refresh_token: String,
-    id_token: Option<String>,
-    scope: String,
-    expires_in: i16,
+pub struct ClientCredentialsTokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub scope: String,
+    pub expires_in: i64,
 }
 
-async fn fetch_token_by_authorization_code<'a>(
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct TokenExchangeTokenResponse {
+    pub access_token: String,
+    pub issued_token_type: Option<String>,
+    #[serde(default)]
+    pub scope: String,
+    pub expires_in: i64,
+}
+
+impl TokenExchangeTokenResponse {
+    /// Reads the `act` claim of the issued access token. The token isn't
+    /// verified, it's only meant for the client's own bookkeeping.
+    pub fn act(&self) -> Result<Option<ActorClaim>, jsonwebtoken::errors::Error> {
+        let key = DecodingKey::from_secret(&[]);
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.validate_aud = false;
+        validation.insecure_disable_signature_validation();
+
+        Ok(
+            decode::<AccessTokenClaims>(&self.access_token, &key, &validation)?
+                .claims
+                .act,
+        )
+    }
+}
+
+pub async fn fetch_token_by_authorization_code<'a>(
     client: &Client,
     parameters: TokenByAuthorizationCodeParameters<'a>,
-) -> Result<CodeTokenResponse, reqwest::Error> {
+) -> Result<CodeTokenResponse, LogtoError> {
     let mut params = HashMap::new();
-    params.insert("client_id", parameters.client_id);
     params.insert("code", parameters.code);
     params.insert("code_verifier", parameters.code_verifier);
     params.insert("redirect_uri", parameters.redirect_uri);
@@ -55,33 +157,105 @@ async fn fetch_token_by_authorization_code<'a>(
         params.insert("resource", resource);
     }
 
-    let response = client
-        .post(parameters.token_endpoint)
-        .form(&params)
+    send_token_request(parameters.dpop, parameters.token_endpoint, || {
+        parameters.client_auth.apply(
+            client.post(parameters.token_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params.clone(),
+        )
+    })
+    .await
+}
+
+pub async fn fetch_token_by_refresh_token<'a>(
+    client: &Client,
+    parameters: TokenByRefreshTokenParameters<'a>,
+) -> Result<RefreshTokenTokenResponse, LogtoError> {
+    let mut params = HashMap::new();
+    params.insert("refresh_token", parameters.refresh_token);
+    params.insert("grant_type", "refresh_token");
+
+    let scope = parameters.scopes.unwrap_or_default().to_string();
+
+    if !scope.is_empty() {
+        params.insert("scope", scope.as_str());
+    }
+
+    if let Some(resource) = parameters.resource {
+        params.insert("resource", resource);
+    }
+
+    if let Some(organization_id) = parameters.organization_id {
+        params.insert("organization_id", organization_id);
+    }
+
+    send_token_request(parameters.dpop, &parameters.token_endpoint, || {
+        parameters.client_auth.apply(
+            client.post(&parameters.token_endpoint),
+            parameters.client_id,
+            &parameters.token_endpoint,
+            params.clone(),
+        )
+    })
+    .await
+}
+
+/// Fetches a token for a machine-to-machine application
+pub async fn fetch_token_by_client_credentials<'a>(
+    client: &Client,
+    parameters: TokenByClientCredentialsParameters<'a>,
+) -> Result<ClientCredentialsTokenResponse, LogtoError> {
+    let mut params = HashMap::new();
+    params.insert("grant_type", "client_credentials");
+
+    let scope = parameters.scopes.unwrap_or_default().to_string();
+
+    if !scope.is_empty() {
+        params.insert("scope", scope.as_str());
+    }
+
+    if let Some(resource) = parameters.resource {
+        params.insert("resource", resource);
+    }
+
+    if let Some(organization_id) = parameters.organization_id {
+        params.insert("organization_id", organization_id);
+    }
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(parameters.token_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params,
+        )?
         .send()
-        .await?
-        .json::<CodeTokenResponse>()
         .await?;
 
-    Ok(response)
+    parse_response(response).await
 }
 
-async fn fetch_token_by_refresh_token(
+/// Exchanges a subject token, e.g. a Logto subject token for impersonation,
+/// see https://datatracker.ietf.org/doc/html/rfc8693#section-2.1
+pub async fn fetch_token_by_token_exchange<'a>(
     client: &Client,
-    parameters: TokenByRefreshTokenParameters,
-) -> Result<RefreshTokenTokenResponse, reqwest::Error> {
+    parameters: TokenExchangeParameters<'a>,
+) -> Result<TokenExchangeTokenResponse, LogtoError> {
     let mut params = HashMap::new();
-    params.insert("client_id", parameters.client_id);
-    params.insert("refresh_token", parameters.refresh_token);
-    params.insert("grant_type", "refresh_token");
+    params.insert("grant_type", TOKEN_EXCHANGE_GRANT_TYPE);
+    params.insert("subject_token", parameters.subject_token);
+    params.insert("subject_token_type", parameters.subject_token_type.as_str());
 
-    let scope = parameters
-        .scopes
-        .as_ref()
-        .map(|val| val.join(" "))
-        .unwrap_or_else(|| "".to_string());
+    if let Some(actor_token) = &parameters.actor_token {
+        params.insert("actor_token", actor_token.token);
+        params.insert("actor_token_type", actor_token.token_type.as_str());
+    }
 
-    if scope.len() > 0 {
+    let scope = parameters.scopes.unwrap_or_default().to_string();
+
+    if !scope.is_empty() {
         params.insert("scope", scope.as_str());
     }
 
@@ -89,15 +263,22 @@ async fn fetch_token_by_refresh_token(
         params.insert("resource", resource);
     }
 
-    let response = client
-        .post(parameters.token_endpoint)
-        .form(&params)
+    if let Some(organization_id) = parameters.organization_id {
+        params.insert("organization_id", organization_id);
+    }
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(parameters.token_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params,
+        )?
         .send()
-        .await?
-        .json::<RefreshTokenTokenResponse>()
         .await?;
 
-    Ok(response)
+    parse_response(response).await
 }
 
 #[cfg(test)]
@@ -156,6 +337,8 @@ mod tests {
             code_verifier: "code_verifier_value",
             code: "code_value",
             resource: Some("resource_value"),
+            client_auth: &ClientAuth::None,
+            dpop: None,
         };
 
         let response = fetch_token_by_authorization_code(&client, params).await;
@@ -213,8 +396,11 @@ mod tests {
             client_id: "client_id_value",
             token_endpoint: endpoint,
             refresh_token: "old_refresh_token_value",
-            scopes: Some(vec!["read", "register", "manage"]),
+            scopes: Some("read register manage".parse().unwrap()),
             resource: Some("resource_value"),
+            organization_id: None,
+            client_auth: &ClientAuth::None,
+            dpop: None,
         };
 
         let response = fetch_token_by_refresh_token(&client, params).await;
@@ -224,4 +410,153 @@ mod tests {
             Err(e) => panic!("Error in fetch_token_by_authorization_code: {}", e),
         }
     }
+
+    #[tokio::test]
+    async fn test_fetch_token_by_client_credentials() {
+        let mut server = mockito::Server::new();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("client_secret".into(), "client_secret_value".into()),
+            Matcher::UrlEncoded("resource".into(), "https://default.logto.app/api".into()),
+            Matcher::UrlEncoded("scope".into(), "all".into()),
+            Matcher::UrlEncoded("grant_type".into(), "client_credentials".into()),
+        ];
+
+        server
+            .mock("POST", "/oidc/token")
+            .match_header("content-type", "application/x-www-form-urlencoded")
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "access_token": "access_token_value",
+                    "token_type": "Bearer",
+                    "scope": "all",
+                    "expires_in": 3600
+                }"#,
+            )
+            .create();
+
+        let expected_token_response = ClientCredentialsTokenResponse {
+            access_token: "access_token_value".to_string(),
+            scope: "all".to_string(),
+            expires_in: 3600,
+        };
+
+        let client = reqwest::Client::new();
+
+        let endpoint = format!("{}/oidc/token", server.url());
+
+        let params = TokenByClientCredentialsParameters {
+            token_endpoint: &endpoint,
+            client_id: "client_id_value",
+            resource: Some("https://default.logto.app/api"),
+            scopes: Some("all".parse().unwrap()),
+            organization_id: None,
+            client_auth: &ClientAuth::ClientSecretPost("client_secret_value".to_string()),
+        };
+
+        let response = fetch_token_by_client_credentials(&client, params).await;
+
+        match response {
+            Ok(r) => assert_eq!(expected_token_response, r),
+            Err(e) => panic!("Error in fetch_token_by_client_credentials: {}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_by_token_exchange() {
+        let mut server = mockito::Server::new();
+
+        let access_token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({
+                "iss": "https://logto.dev/oidc",
+                "sub": "user",
+                "aud": "https://default.logto.app/api",
+                "exp": 4102444800u64,
+                "iat": 1700000000,
+                "act": { "sub": "support_agent" },
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("grant_type".into(), TOKEN_EXCHANGE_GRANT_TYPE.into()),
+            Matcher::UrlEncoded("subject_token".into(), "subject_token_value".into()),
+            Matcher::UrlEncoded(
+                "subject_token_type".into(),
+                TokenType::AccessToken.as_str().into(),
+            ),
+            Matcher::UrlEncoded("actor_token".into(), "actor_token_value".into()),
+            Matcher::UrlEncoded(
+                "actor_token_type".into(),
+                TokenType::AccessToken.as_str().into(),
+            ),
+            Matcher::UrlEncoded("resource".into(), "https://default.logto.app/api".into()),
+        ];
+
+        server
+            .mock("POST", "/oidc/token")
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{
+                    "access_token": "{}",
+                    "issued_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                    "token_type": "Bearer",
+                    "expires_in": 3600
+                }}"#,
+                access_token
+            ))
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token", server.url());
+
+        let params = TokenExchangeParameters {
+            token_endpoint: &endpoint,
+            client_id: "client_id_value",
+            subject_token: "subject_token_value",
+            subject_token_type: TokenType::AccessToken,
+            actor_token: Some(ActorToken {
+                token: "actor_token_value",
+                token_type: TokenType::AccessToken,
+            }),
+            resource: Some("https://default.logto.app/api"),
+            scopes: None,
+            organization_id: None,
+            client_auth: &ClientAuth::None,
+        };
+
+        let response = fetch_token_by_token_exchange(&client, params)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.act().unwrap(),
+            Some(ActorClaim {
+                sub: "support_agent".to_string(),
+                act: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_long_expires_in() {
+        let response: RefreshTokenTokenResponse = serde_json::from_str(
+            r#"{
+                "access_token": "access_token_value",
+                "refresh_token": "refresh_token_value",
+                "scope": "openid",
+                "expires_in": 86400
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.expires_in, 86400);
+    }
 }
@@ -0,0 +1,336 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::core::{
+    access_token::now,
+    client_auth::ClientAuth,
+    error::{parse_response, LogtoError},
+    scope::Scopes,
+};
+
+/// Tells the server which kind of token it's given,
+/// see https://datatracker.ietf.org/doc/html/rfc7009#section-2.1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenTypeHint {
+    AccessToken,
+    RefreshToken,
+}
+
+impl TokenTypeHint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AccessToken => "access_token",
+            Self::RefreshToken => "refresh_token",
+        }
+    }
+}
+
+pub struct IntrospectionParameters<'a> {
+    pub introspection_endpoint: &'a str,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub token: &'a str,
+    pub token_type_hint: Option<TokenTypeHint>,
+    pub client_auth: &'a ClientAuth,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Only `active` is guaranteed, inactive tokens come without other members,
+/// see https://datatracker.ietf.org/doc/html/rfc7662#section-2.2
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(default)]
+    pub scope: Scopes,
+    pub client_id: Option<String>,
+    pub username: Option<String>,
+    pub token_type: Option<String>,
+    pub exp: Option<u64>,
+    pub iat: Option<u64>,
+    pub nbf: Option<u64>,
+    pub sub: Option<String>,
+    pub aud: Option<Audience>,
+    pub iss: Option<String>,
+    pub jti: Option<String>,
+}
+
+/// Asks the authorization server whether a token is active,
+/// see https://datatracker.ietf.org/doc/html/rfc7662#section-2.1
+pub async fn introspect<'a>(
+    client: &Client,
+    parameters: IntrospectionParameters<'a>,
+) -> Result<IntrospectionResponse, LogtoError> {
+    let mut params = HashMap::new();
+    params.insert("token", parameters.token);
+
+    if let Some(token_type_hint) = parameters.token_type_hint {
+        params.insert("token_type_hint", token_type_hint.as_str());
+    }
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(parameters.introspection_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params,
+        )?
+        .send()
+        .await?;
+
+    parse_response(response).await
+}
+
+pub struct IntrospectionCacheOptions {
+    pub introspection_endpoint: String,
+    /// Audience of client assertions
+    pub token_endpoint: String,
+    pub client_id: String,
+    pub client_auth: ClientAuth,
+    /// How long an active result is served from the cache at most, a
+    /// revocation shows after this delay
+    pub positive_ttl: Duration,
+    /// How long an inactive result is served from the cache
+    pub negative_ttl: Duration,
+}
+
+/// Caches introspection results by token hash. Active results are kept until
+/// the token's `exp` or for `positive_ttl`, whichever comes first.
+pub struct IntrospectionCache {
+    client: Client,
+    options: IntrospectionCacheOptions,
+    entries: Mutex<HashMap<String, (IntrospectionResponse, u64)>>,
+}
+
+impl IntrospectionCache {
+    pub fn new(client: Client, options: IntrospectionCacheOptions) -> Self {
+        Self {
+            client,
+            options,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn introspect(
+        &self,
+        token: &str,
+        token_type_hint: Option<TokenTypeHint>,
+    ) -> Result<IntrospectionResponse, LogtoError> {
+        let key = token_hash(token);
+
+        if let Some((response, expires_at)) = self.entries().get(&key) {
+            if *expires_at > now() {
+                return Ok(response.clone());
+            }
+        }
+
+        let response = introspect(
+            &self.client,
+            IntrospectionParameters {
+                introspection_endpoint: &self.options.introspection_endpoint,
+                token_endpoint: &self.options.token_endpoint,
+                client_id: &self.options.client_id,
+                token,
+                token_type_hint,
+                client_auth: &self.options.client_auth,
+            },
+        )
+        .await?;
+
+        let expires_at = match (response.active, response.exp) {
+            (true, exp) => {
+                let max_expires_at = now() + self.options.positive_ttl.as_secs();
+                exp.map_or(max_expires_at, |exp| exp.min(max_expires_at))
+            }
+            (false, _) => now() + self.options.negative_ttl.as_secs(),
+        };
+
+        let mut entries = self.entries();
+        let now = now();
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        entries.insert(key, (response.clone(), expires_at));
+
+        Ok(response)
+    }
+
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, (IntrospectionResponse, u64)>> {
+        self.entries
+            .lock()
+            .expect("introspection cache lock poisoned")
+    }
+}
+
+// Keeps raw tokens out of memory dumps of the cache
+fn token_hash(token: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_introspect() {
+        let mut server = mockito::Server::new();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("token".into(), "token_value".into()),
+            Matcher::UrlEncoded("token_type_hint".into(), "access_token".into()),
+        ];
+
+        server
+            .mock("POST", "/oidc/token/introspection")
+            .match_header(
+                "authorization",
+                "Basic Y2xpZW50X2lkX3ZhbHVlOmNsaWVudF9zZWNyZXRfdmFsdWU=",
+            )
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "active": true,
+                    "scope": "read write",
+                    "client_id": "client_id_value",
+                    "sub": "user",
+                    "aud": ["https://api.example.com"],
+                    "exp": 4102444800
+                }"#,
+            )
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token/introspection", server.url());
+
+        let response = introspect(
+            &client,
+            IntrospectionParameters {
+                introspection_endpoint: &endpoint,
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_id: "client_id_value",
+                token: "token_value",
+                token_type_hint: Some(TokenTypeHint::AccessToken),
+                client_auth: &ClientAuth::ClientSecretBasic("client_secret_value".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(response.active);
+        assert_eq!(response.scope.to_string(), "read write");
+        assert_eq!(
+            response.aud,
+            Some(Audience::Many(vec!["https://api.example.com".to_string()]))
+        );
+    }
+
+    fn cache(
+        server: &mockito::Server,
+        positive_ttl: Duration,
+        negative_ttl: Duration,
+    ) -> IntrospectionCache {
+        IntrospectionCache::new(
+            reqwest::Client::new(),
+            IntrospectionCacheOptions {
+                introspection_endpoint: format!("{}/oidc/token/introspection", server.url()),
+                token_endpoint: "https://logto.dev/oidc/token".to_string(),
+                client_id: "client_id_value".to_string(),
+                client_auth: ClientAuth::None,
+                positive_ttl,
+                negative_ttl,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn caches_until_exp() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token/introspection")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"active": true, "exp": {}}}"#, now() + 3600))
+            .expect(1)
+            .create();
+
+        let cache = cache(&server, Duration::from_secs(300), Duration::ZERO);
+
+        assert!(cache.introspect("token_value", None).await.unwrap().active);
+        assert!(cache.introspect("token_value", None).await.unwrap().active);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn positive_results_honor_ttl() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token/introspection")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"active": true, "exp": {}}}"#, now() + 3600))
+            .expect(2)
+            .create();
+
+        // The entry is already older than a zero TTL on the second call
+        let cache = cache(&server, Duration::ZERO, Duration::from_secs(60));
+
+        assert!(cache.introspect("token_value", None).await.unwrap().active);
+        assert!(cache.introspect("token_value", None).await.unwrap().active);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn negative_results_honor_ttl() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token/introspection")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"active": false}"#)
+            .expect(3)
+            .create();
+
+        // Both calls reach the server with a zero TTL, only the first otherwise
+        let expiring = cache(&server, Duration::ZERO, Duration::ZERO);
+        assert!(
+            !expiring
+                .introspect("token_value", None)
+                .await
+                .unwrap()
+                .active
+        );
+        assert!(
+            !expiring
+                .introspect("token_value", None)
+                .await
+                .unwrap()
+                .active
+        );
+
+        let cached = cache(&server, Duration::ZERO, Duration::from_secs(60));
+        cached.introspect("token_value", None).await.unwrap();
+        cached.introspect("token_value", None).await.unwrap();
+
+        mock.assert();
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use url::{form_urlencoded, Url};
+
+use crate::{
+    core::session_store::SessionStore,
+    utils::verify_logout_token::{verify_logout_token, LogoutTokenError, LogoutTokenParameters},
+};
+
+/// Handles a back-channel logout request: verifies its `logout_token` and ends
+/// the sessions it targets. Answer 200 on `Ok` and 400 on `Err`,
+/// see https://openid.net/specs/openid-connect-backchannel-1_0.html#BCResponse
+pub fn handle_back_channel_logout(
+    session_store: &impl SessionStore,
+    params: LogoutTokenParameters,
+) -> Result<usize, LogoutTokenError> {
+    let claims = verify_logout_token(params)?;
+
+    Ok(session_store.end_sessions(claims.sid.as_deref(), claims.sub.as_deref()))
+}
+
+pub struct FrontChannelLogoutParameters<'a> {
+    /// Query string of the logout request, with or without the leading `?`
+    pub query: &'a str,
+    pub issuer: &'a str,
+    /// `sid` of the session the request came with, e.g. read from its cookie.
+    /// Only that session can be ended.
+    pub current_sid: Option<&'a str>,
+}
+
+/// What to answer the front-channel logout request with
+#[derive(Debug, PartialEq)]
+pub struct FrontChannelLogoutResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: &'static str,
+}
+
+/// Handles a front-channel logout request rendered by the authorization server
+/// in an iframe: checks `iss`, and `sid` against the current session, and ends
+/// that session. The response
+/// can't be cached and may only be framed by the issuer,
+/// see https://openid.net/specs/openid-connect-frontchannel-1_0.html#RPLogout
+pub fn handle_front_channel_logout(
+    session_store: &impl SessionStore,
+    params: FrontChannelLogoutParameters,
+) -> FrontChannelLogoutResponse {
+    let query: HashMap<String, String> =
+        form_urlencoded::parse(params.query.trim_start_matches('?').as_bytes())
+            .into_owned()
+            .collect();
+
+    let sid = match (query.get("iss"), query.get("sid")) {
+        (Some(iss), Some(sid))
+            if iss == params.issuer
+                && params.current_sid == Some(sid.as_str())
+                && session_store.contains(sid) =>
+        {
+            sid
+        }
+        _ => return front_channel_response(400, params.issuer),
+    };
+
+    session_store.end_sessions(Some(sid), None);
+
+    front_channel_response(200, params.issuer)
+}
+
+fn front_channel_response(status: u16, issuer: &str) -> FrontChannelLogoutResponse {
+    let frame_ancestors = match Url::parse(issuer) {
+        Ok(url) => url.origin().ascii_serialization(),
+        Err(_) => "'none'".to_string(),
+    };
+
+    FrontChannelLogoutResponse {
+        status,
+        headers: vec![
+            ("Cache-Control", "no-cache, no-store".to_string()),
+            ("Pragma", "no-cache".to_string()),
+            (
+                "Content-Security-Policy",
+                format!("frame-ancestors {}", frame_ancestors),
+            ),
+        ],
+        body: "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::session_store::InMemorySessionStore;
+
+    #[test]
+    fn front_channel_logout_ends_session() {
+        let store = InMemorySessionStore::new();
+        store.insert("sid_value", "user");
+
+        let response = handle_front_channel_logout(
+            &store,
+            FrontChannelLogoutParameters {
+                query: "?iss=https%3A%2F%2Flogto.dev%2Foidc&sid=sid_value",
+                issuer: "https://logto.dev/oidc",
+                current_sid: Some("sid_value"),
+            },
+        );
+
+        assert_eq!(response.status, 200);
+        assert!(response.headers.contains(&(
+            "Content-Security-Policy",
+            "frame-ancestors https://logto.dev".to_string()
+        )));
+        assert!(!store.contains("sid_value"));
+    }
+
+    #[test]
+    fn front_channel_logout_rejects_other_issuer() {
+        let store = InMemorySessionStore::new();
+        store.insert("sid_value", "user");
+
+        let response = handle_front_channel_logout(
+            &store,
+            FrontChannelLogoutParameters {
+                query: "iss=https%3A%2F%2Fevil.dev%2Foidc&sid=sid_value",
+                issuer: "https://logto.dev/oidc",
+                current_sid: Some("sid_value"),
+            },
+        );
+
+        assert_eq!(response.status, 400);
+        assert!(store.contains("sid_value"));
+    }
+
+    #[test]
+    fn front_channel_logout_rejects_other_session() {
+        let store = InMemorySessionStore::new();
+        store.insert("sid_value", "user");
+        store.insert("other_sid_value", "other_user");
+
+        let response = handle_front_channel_logout(
+            &store,
+            FrontChannelLogoutParameters {
+                query: "iss=https%3A%2F%2Flogto.dev%2Foidc&sid=other_sid_value",
+                issuer: "https://logto.dev/oidc",
+                current_sid: Some("sid_value"),
+            },
+        );
+
+        assert_eq!(response.status, 400);
+        assert!(store.contains("sid_value"));
+        assert!(store.contains("other_sid_value"));
+    }
+}
@@ -0,0 +1,312 @@
+use std::{future::Future, time::Duration};
+
+use reqwest::Client;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+use crate::{
+    core::{
+        client_auth::ClientAuth,
+        error::LogtoError,
+        fetch_token::{
+            fetch_token_by_authorization_code, CodeTokenResponse,
+            TokenByAuthorizationCodeParameters,
+        },
+        scope::Scopes,
+        sign_in::{generate_signin_uri, SignInUriGenerationOptions},
+    },
+    utils::{
+        generators::{generate_code_challenge, generate_code_verifier, generate_state},
+        verify_and_parse_code_from_callback_uri::{
+            verify_and_parse_code_from_callback_uri, UnverifiedUris,
+        },
+    },
+};
+
+const CALLBACK_PATH: &str = "/callback";
+const SUCCESS_PAGE: &str =
+    "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Signed in</title></head>\
+<body><p>You are signed in. You can close this tab.</p></body></html>";
+const FAILURE_PAGE: &str =
+    "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Sign-in failed</title></head>\
+<body><p>Sign-in failed. You can close this tab and try again.</p></body></html>";
+
+pub struct LoopbackSignInOptions<'a> {
+    pub authorization_endpoint: &'a str,
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub scopes: Option<Scopes>,
+    pub resources: Option<Vec<&'a str>>,
+    pub client_auth: &'a ClientAuth,
+    /// How long to wait for the browser to come back to the redirect URI
+    pub timeout: Duration,
+}
+
+/// A sign-in waiting for its callback on a loopback port,
+/// see https://datatracker.ietf.org/doc/html/rfc8252#section-7.3
+pub struct LoopbackSignIn<'a> {
+    listener: TcpListener,
+    options: LoopbackSignInOptions<'a>,
+    redirect_uri: String,
+    authorization_uri: String,
+    code_verifier: String,
+    state: String,
+}
+
+/// Binds an ephemeral `127.0.0.1` port and builds the authorization URL
+/// redirecting to it. Open `authorization_uri()` in the browser, then call
+/// `complete` to wait for the callback.
+pub async fn sign_in_with_loopback(
+    options: LoopbackSignInOptions<'_>,
+) -> Result<LoopbackSignIn<'_>, LogtoError> {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}{}",
+        listener.local_addr()?.port(),
+        CALLBACK_PATH
+    );
+
+    let code_verifier = generate_code_verifier();
+    let code_challenge = generate_code_challenge(code_verifier.clone());
+    let state = generate_state();
+
+    let authorization_uri = generate_signin_uri(SignInUriGenerationOptions {
+        authorization_endpoint: options.authorization_endpoint.to_string(),
+        client_id: options.client_id,
+        redirect_uri: &redirect_uri,
+        code_challenge: &code_challenge,
+        state: &state,
+        scopes: options.scopes.clone(),
+        resources: options.resources.clone(),
+        ..Default::default()
+    })
+    .map_err(|e| LogtoError::InvalidUrl(e.to_string()))?;
+
+    Ok(LoopbackSignIn {
+        listener,
+        options,
+        redirect_uri,
+        authorization_uri,
+        code_verifier,
+        state,
+    })
+}
+
+impl<'a> LoopbackSignIn<'a> {
+    pub fn authorization_uri(&self) -> &str {
+        &self.authorization_uri
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Accepts a single callback and exchanges its code for tokens. Fails
+    /// with `TimedOut` after the configured timeout, or with `Cancelled` once
+    /// `cancel` resolves.
+    pub async fn complete(
+        self,
+        client: &Client,
+        cancel: impl Future<Output = ()>,
+    ) -> Result<CodeTokenResponse, LogtoError> {
+        let code = tokio::select! {
+            result = timeout(self.options.timeout, self.accept_callback()) => {
+                result.map_err(|_| LogtoError::TimedOut)??
+            }
+            _ = cancel => return Err(LogtoError::Cancelled),
+        };
+
+        fetch_token_by_authorization_code(
+            client,
+            TokenByAuthorizationCodeParameters {
+                token_endpoint: self.options.token_endpoint,
+                code: &code,
+                code_verifier: &self.code_verifier,
+                client_id: self.options.client_id,
+                redirect_uri: &self.redirect_uri,
+                resource: None,
+                client_auth: self.options.client_auth,
+                dpop: None,
+            },
+        )
+        .await
+    }
+
+    async fn accept_callback(&self) -> Result<String, LogtoError> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+
+            // Browsers may ask for other paths first, e.g. `/favicon.ico`
+            let Some(path) = read_request_path(&mut stream).await? else {
+                respond(&mut stream, "404 Not Found", "").await?;
+                continue;
+            };
+
+            let origin = self.redirect_uri.trim_end_matches(CALLBACK_PATH);
+            let result = verify_and_parse_code_from_callback_uri(UnverifiedUris {
+                callback_uri: format!("{}{}", origin, path),
+                redirect_uri: self.redirect_uri.clone(),
+                state: self.state.clone(),
+            });
+
+            let page = match result {
+                Ok(_) => SUCCESS_PAGE,
+                Err(_) => FAILURE_PAGE,
+            };
+            respond(&mut stream, "200 OK", page).await?;
+
+            return Ok(result?);
+        }
+    }
+}
+
+/// Returns the request target when it's a GET of the callback path
+async fn read_request_path(stream: &mut TcpStream) -> Result<Option<String>, LogtoError> {
+    let mut request_line = String::new();
+    BufReader::new(stream).read_line(&mut request_line).await?;
+
+    let mut parts = request_line.split_whitespace();
+
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path))
+            if path == CALLBACK_PATH || path.starts_with(&format!("{}?", CALLBACK_PATH)) =>
+        {
+            Ok(Some(path.to_string()))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<(), LogtoError> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+    use url::Url;
+
+    use super::*;
+
+    fn options<'a>(
+        token_endpoint: &'a str,
+        client_auth: &'a ClientAuth,
+    ) -> LoopbackSignInOptions<'a> {
+        LoopbackSignInOptions {
+            authorization_endpoint: "https://logto.dev/oidc/auth",
+            token_endpoint,
+            client_id: "client_id_value",
+            scopes: None,
+            resources: None,
+            client_auth,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn exchanges_code_from_callback() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "authorization_code".into()),
+                Matcher::UrlEncoded("code".into(), "code_value".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "access_token": "access_token_value",
+                    "refresh_token": "refresh_token_value",
+                    "id_token": "id_token_value",
+                    "scope": "openid offline_access",
+                    "expires_in": 3600
+                }"#,
+            )
+            .create();
+
+        let token_endpoint = format!("{}/oidc/token", server.url());
+        let client_auth = ClientAuth::None;
+        let sign_in = sign_in_with_loopback(options(&token_endpoint, &client_auth))
+            .await
+            .unwrap();
+
+        let authorization_uri = Url::parse(sign_in.authorization_uri()).unwrap();
+        let (_, state) = authorization_uri
+            .query_pairs()
+            .find(|(key, _)| key == "state")
+            .unwrap();
+        assert!(authorization_uri
+            .query_pairs()
+            .any(|(key, value)| key == "redirect_uri" && value == sign_in.redirect_uri()));
+
+        let callback_uri = format!("{}?code=code_value&state={}", sign_in.redirect_uri(), state);
+        let browser = tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let favicon = callback_uri.replace(CALLBACK_PATH, "/favicon.ico");
+
+            assert_eq!(client.get(favicon).send().await.unwrap().status(), 404);
+            client
+                .get(callback_uri)
+                .send()
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap()
+        });
+
+        let client = reqwest::Client::new();
+        let response = sign_in
+            .complete(&client, std::future::pending())
+            .await
+            .unwrap();
+
+        assert_eq!(response.access_token, "access_token_value");
+        assert!(browser.await.unwrap().contains("You can close this tab"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn rejects_callback_with_wrong_state() {
+        let client_auth = ClientAuth::None;
+        let sign_in = sign_in_with_loopback(options("https://logto.dev/oidc/token", &client_auth))
+            .await
+            .unwrap();
+
+        let callback_uri = format!("{}?code=code_value&state=forged", sign_in.redirect_uri());
+        tokio::spawn(reqwest::get(callback_uri));
+
+        let client = reqwest::Client::new();
+        let response = sign_in.complete(&client, std::future::pending()).await;
+
+        assert!(matches!(response, Err(LogtoError::InvalidCallback(_))));
+    }
+
+    #[tokio::test]
+    async fn stops_when_cancelled() {
+        let client_auth = ClientAuth::None;
+        let sign_in = sign_in_with_loopback(options("https://logto.dev/oidc/token", &client_auth))
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let response = sign_in.complete(&client, async {}).await;
+
+        assert!(matches!(response, Err(LogtoError::Cancelled)));
+    }
+}
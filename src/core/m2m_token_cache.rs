@@ -0,0 +1,177 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use reqwest::Client;
+use tokio::sync::Mutex;
+
+use crate::core::{
+    access_token::{build_access_token_key, now, AccessToken},
+    client_auth::ClientAuth,
+    error::LogtoError,
+    fetch_token::{fetch_token_by_client_credentials, TokenByClientCredentialsParameters},
+    scope::Scopes,
+};
+
+pub struct M2mTokenCacheOptions {
+    pub token_endpoint: String,
+    pub client_id: String,
+    pub client_auth: ClientAuth,
+    /// Tokens expiring within this window are fetched again
+    pub refresh_leeway: Duration,
+}
+
+/// Caches client credentials tokens of a machine-to-machine application per
+/// resource, scopes and organization. Concurrent callers of the same key wait
+/// for a single token request instead of each fetching their own, other keys
+/// aren't held up meanwhile.
+pub struct M2mTokenCache {
+    client: Client,
+    options: M2mTokenCacheOptions,
+    entries: Mutex<HashMap<String, Arc<Mutex<Option<AccessToken>>>>>,
+}
+
+impl M2mTokenCache {
+    pub fn new(client: Client, options: M2mTokenCacheOptions) -> Self {
+        Self {
+            client,
+            options,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_token(
+        &self,
+        resource: Option<&str>,
+        scopes: Option<&Scopes>,
+        organization_id: Option<&str>,
+    ) -> Result<AccessToken, LogtoError> {
+        let key = build_access_token_key(resource, organization_id, scopes);
+        let entry = self.entries.lock().await.entry(key).or_default().clone();
+        let mut entry = entry.lock().await;
+
+        if let Some(access_token) = entry.as_ref() {
+            if !access_token.expires_within(self.options.refresh_leeway) {
+                return Ok(access_token.clone());
+            }
+        }
+
+        let response = fetch_token_by_client_credentials(
+            &self.client,
+            TokenByClientCredentialsParameters {
+                token_endpoint: &self.options.token_endpoint,
+                client_id: &self.options.client_id,
+                resource,
+                scopes: scopes.cloned(),
+                organization_id,
+                client_auth: &self.options.client_auth,
+            },
+        )
+        .await?;
+
+        let access_token = AccessToken {
+            token: response.access_token,
+            scope: response.scope.parse().unwrap_or_default(),
+            expires_at: now() + response.expires_in.max(0) as u64,
+        };
+
+        *entry = Some(access_token.clone());
+
+        Ok(access_token)
+    }
+
+    /// Drops every cached token, e.g. after the application secret rotated
+    pub async fn clear(&self) {
+        self.entries.lock().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(server: &mockito::Server, refresh_leeway: Duration) -> M2mTokenCache {
+        M2mTokenCache::new(
+            reqwest::Client::new(),
+            M2mTokenCacheOptions {
+                token_endpoint: format!("{}/oidc/token", server.url()),
+                client_id: "client_id_value".to_string(),
+                client_auth: ClientAuth::ClientSecretBasic("client_secret_value".to_string()),
+                refresh_leeway,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_request() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"access_token": "access_token_value", "scope": "all", "expires_in": 3600}"#,
+            )
+            .expect(1)
+            .create();
+
+        let cache = cache(&server, Duration::from_secs(60));
+        let scopes: Scopes = "all".parse().unwrap();
+
+        let (first, second) = tokio::join!(
+            cache.get_token(Some("https://default.logto.app/api"), Some(&scopes), None),
+            cache.get_token(Some("https://default.logto.app/api"), Some(&scopes), None),
+        );
+
+        assert_eq!(first.unwrap().token, "access_token_value");
+        assert_eq!(second.unwrap().token, "access_token_value");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn refreshes_ahead_of_expiry() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token": "access_token_value", "expires_in": 30}"#)
+            .expect(2)
+            .create();
+
+        let cache = cache(&server, Duration::from_secs(60));
+
+        cache.get_token(None, None, None).await.unwrap();
+        cache.get_token(None, None, None).await.unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn keys_are_cached_separately() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token": "access_token_value", "expires_in": 3600}"#)
+            .expect(2)
+            .create();
+
+        let cache = cache(&server, Duration::from_secs(60));
+
+        let (first, second) = tokio::join!(
+            cache.get_token(Some("https://first.example.com"), None, None),
+            cache.get_token(Some("https://second.example.com"), None, None),
+        );
+        first.unwrap();
+        second.unwrap();
+        cache
+            .get_token(Some("https://first.example.com"), None, None)
+            .await
+            .unwrap();
+
+        mock.assert();
+    }
+}
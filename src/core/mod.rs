@@ -1,5 +1,17 @@
-mod fetch_token;
-mod oicd_config;
-mod revoke;
-mod sign_in;
-mod sign_out;
+pub mod access_token;
+pub mod client_auth;
+pub mod device_authorization;
+pub mod dpop;
+pub mod error;
+pub mod fetch_token;
+pub mod introspection;
+pub mod logout;
+pub mod loopback;
+pub mod m2m_token_cache;
+pub mod oicd_config;
+pub mod pushed_authorization;
+pub mod revoke;
+pub mod scope;
+pub mod session_store;
+pub mod sign_in;
+pub mod sign_out;
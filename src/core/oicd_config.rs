@@ -2,16 +2,20 @@ use reqwest::Client;
 use serde::Deserialize;
 
 #[derive(Debug, PartialEq, Deserialize)]
-struct OidcConfigResponse {
-    authorization_endpoint: String,
-    token_endpoint: String,
-    end_session_endpoint: String,
-    revocation_endpoint: String,
-    jwks_uri: String,
-    issuer: String,
+pub struct OidcConfigResponse {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub end_session_endpoint: String,
+    pub revocation_endpoint: String,
+    pub jwks_uri: String,
+    pub issuer: String,
+    pub device_authorization_endpoint: Option<String>,
+    /// Only advertised when the tenant supports pushed authorization requests
+    pub pushed_authorization_request_endpoint: Option<String>,
+    pub introspection_endpoint: Option<String>,
 }
 
-async fn fetch_oidc_config(
+pub async fn fetch_oidc_config(
     client: &Client,
     endpoint: &str,
 ) -> Result<OidcConfigResponse, reqwest::Error> {
@@ -43,7 +47,8 @@ mod tests {
                     "end_session_endpoint": "foo",
                     "revocation_endpoint": "foo",
                     "jwks_uri": "foo",
-                    "issuer": "foo"
+                    "issuer": "foo",
+                    "device_authorization_endpoint": "foo"
                 }"#,
             )
             .create();
@@ -55,6 +60,9 @@ mod tests {
             revocation_endpoint: "foo".to_string(),
             jwks_uri: "foo".to_string(),
             issuer: "foo".to_string(),
+            device_authorization_endpoint: Some("foo".to_string()),
+            pushed_authorization_request_endpoint: None,
+            introspection_endpoint: None,
         };
 
         let client = reqwest::Client::new();
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+use crate::core::{
+    client_auth::ClientAuth,
+    error::{parse_response, LogtoError},
+    sign_in::{build_signin_params, generate_signin_uri, SignInUriGenerationOptions},
+};
+
+pub struct PushedAuthorizationParameters<'a> {
+    /// `pushed_authorization_request_endpoint` from the OIDC configuration,
+    /// `None` falls back to a plain sign-in URI
+    pub pushed_authorization_request_endpoint: Option<&'a str>,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_auth: &'a ClientAuth,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct PushedAuthorizationResponse {
+    pub request_uri: String,
+    pub expires_in: u64,
+}
+
+/// Pushes the sign-in parameters to the authorization server and returns a
+/// sign-in URI carrying only `client_id` and `request_uri`,
+/// see https://datatracker.ietf.org/doc/html/rfc9126
+pub async fn generate_signin_uri_with_par<'a>(
+    client: &Client,
+    parameters: PushedAuthorizationParameters<'a>,
+    options: SignInUriGenerationOptions<'a>,
+) -> Result<String, LogtoError> {
+    let Some(endpoint) = parameters.pushed_authorization_request_endpoint else {
+        return generate_signin_uri(options).map_err(|e| LogtoError::InvalidUrl(e.to_string()));
+    };
+
+    let mut url = Url::parse(&options.authorization_endpoint)
+        .map_err(|e| LogtoError::InvalidUrl(e.to_string()))?;
+    let client_id = options.client_id;
+
+    let signin_params = build_signin_params(options);
+    let params: HashMap<&str, &str> = signin_params
+        .iter()
+        .filter(|(key, _)| *key != "client_id")
+        .map(|(key, value)| (*key, value.as_str()))
+        .collect();
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(endpoint),
+            client_id,
+            parameters.token_endpoint,
+            params,
+        )?
+        .send()
+        .await?;
+
+    let response: PushedAuthorizationResponse = parse_response(response).await?;
+
+    url.query_pairs_mut()
+        .append_pair("client_id", client_id)
+        .append_pair("request_uri", &response.request_uri);
+
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+
+    fn options<'a>() -> SignInUriGenerationOptions<'a> {
+        SignInUriGenerationOptions {
+            authorization_endpoint: "https://logto.dev/oidc/auth".to_string(),
+            client_id: "client_id_value",
+            redirect_uri: "https://example.com/callback",
+            code_challenge: "code_challenge_value",
+            state: "state_value",
+            resources: Some(vec!["https://default.logto.app/api"]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn pushes_signin_params() {
+        let mut server = mockito::Server::new();
+
+        let body_matchers = vec![
+            Matcher::UrlEncoded("redirect_uri".into(), "https://example.com/callback".into()),
+            Matcher::UrlEncoded("code_challenge".into(), "code_challenge_value".into()),
+            Matcher::UrlEncoded("state".into(), "state_value".into()),
+            Matcher::UrlEncoded("resource".into(), "https://default.logto.app/api".into()),
+            Matcher::UrlEncoded("client_secret".into(), "client_secret_value".into()),
+        ];
+
+        let mock = server
+            .mock("POST", "/oidc/request")
+            .match_body(Matcher::AllOf(body_matchers))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"request_uri": "urn:ietf:params:oauth:request_uri:abc", "expires_in": 60}"#,
+            )
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/request", server.url());
+
+        let uri = generate_signin_uri_with_par(
+            &client,
+            PushedAuthorizationParameters {
+                pushed_authorization_request_endpoint: Some(&endpoint),
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_auth: &ClientAuth::ClientSecretPost("client_secret_value".to_string()),
+            },
+            options(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            uri,
+            "https://logto.dev/oidc/auth?client_id=client_id_value&request_uri=urn%3Aietf%3Aparams%3Aoauth%3Arequest_uri%3Aabc"
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn falls_back_without_par_endpoint() {
+        let client = reqwest::Client::new();
+
+        let uri = generate_signin_uri_with_par(
+            &client,
+            PushedAuthorizationParameters {
+                pushed_authorization_request_endpoint: None,
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_auth: &ClientAuth::None,
+            },
+            options(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(uri, generate_signin_uri(options()).unwrap());
+    }
+}
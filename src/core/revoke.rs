@@ -1,30 +1,105 @@
 use std::collections::HashMap;
 
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 
-struct RevocationParams<'a> {
-    revocation_endpoint: &'a str,
-    client_id: &'a str,
-    token: &'a str,
+use crate::core::{
+    access_token::AccessTokenMap,
+    client_auth::ClientAuth,
+    error::{check_response, LogtoError},
+    introspection::TokenTypeHint,
+};
+
+pub struct RevocationParams<'a> {
+    pub revocation_endpoint: &'a str,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub token: &'a str,
+    pub token_type_hint: Option<TokenTypeHint>,
+    pub client_auth: &'a ClientAuth,
+}
+
+pub struct RevokeAllParameters<'a> {
+    pub revocation_endpoint: &'a str,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    pub refresh_token: Option<&'a str>,
+    pub client_auth: &'a ClientAuth,
 }
 
-async fn revoke<'a>(
+/// Revokes an access or refresh token,
+/// see https://datatracker.ietf.org/doc/html/rfc7009#section-2
+pub async fn revoke<'a>(
     client: &Client,
     parameters: RevocationParams<'a>,
-) -> Result<(), reqwest::Error> {
+) -> Result<(), LogtoError> {
     let mut params = HashMap::new();
-    params.insert("client_id", parameters.client_id);
     params.insert("token", parameters.token);
 
-    match client
-        .post(parameters.revocation_endpoint)
-        .form(&params)
+    if let Some(token_type_hint) = parameters.token_type_hint {
+        params.insert("token_type_hint", token_type_hint.as_str());
+    }
+
+    let response = parameters
+        .client_auth
+        .apply(
+            client.post(parameters.revocation_endpoint),
+            parameters.client_id,
+            parameters.token_endpoint,
+            params,
+        )?
         .send()
-        .await
-    {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e),
+        .await?;
+
+    match check_response(response).await?.status() {
+        StatusCode::OK => Ok(()),
+        status => Err(LogtoError::UnexpectedStatus(status)),
+    }
+}
+
+/// Revokes the refresh token and every unexpired access token in the map, e.g.
+/// on sign-out. The map is cleared even if a revocation fails, the first
+/// error is returned once all tokens were tried.
+pub async fn revoke_all<'a>(
+    client: &Client,
+    access_token_map: &mut AccessTokenMap,
+    parameters: RevokeAllParameters<'a>,
+) -> Result<(), LogtoError> {
+    let mut tokens: Vec<(&str, TokenTypeHint)> = access_token_map
+        .values()
+        .filter(|access_token| !access_token.is_expired())
+        .map(|access_token| (access_token.token.as_str(), TokenTypeHint::AccessToken))
+        .collect();
+
+    if let Some(refresh_token) = parameters.refresh_token {
+        tokens.insert(0, (refresh_token, TokenTypeHint::RefreshToken));
+    }
+
+    let mut result = Ok(());
+
+    for (token, token_type_hint) in tokens {
+        let revoked = revoke(
+            client,
+            RevocationParams {
+                revocation_endpoint: parameters.revocation_endpoint,
+                token_endpoint: parameters.token_endpoint,
+                client_id: parameters.client_id,
+                token,
+                token_type_hint: Some(token_type_hint),
+                client_auth: parameters.client_auth,
+            },
+        )
+        .await;
+
+        if result.is_ok() {
+            result = revoked;
+        }
     }
+
+    access_token_map.clear();
+
+    result
 }
 
 #[cfg(test)]
@@ -32,21 +107,22 @@ mod tests {
     use mockito::Matcher;
 
     use super::*;
+    use crate::core::access_token::{now, AccessToken};
 
     #[tokio::test]
     async fn should_revoke() {
         let mut server = mockito::Server::new();
         let body_matcher = vec![
-            Matcher::UrlEncoded("client_id".into(), "client_id_value".into()),
+            Matcher::UrlEncoded("client_id".into(), "client_id".into()),
             Matcher::UrlEncoded("token".into(), "token".into()),
+            Matcher::UrlEncoded("token_type_hint".into(), "refresh_token".into()),
         ];
 
-        server
-            .mock("POST", "/oidc/token")
+        let mock = server
+            .mock("POST", "/oidc/token/revocation")
             .match_header("content-type", "application/x-www-form-urlencoded")
             .match_body(Matcher::AllOf(body_matcher))
-            .with_status(201)
-            .with_header("content-type", "application/json")
+            .with_status(200)
             .expect(1)
             .create();
 
@@ -57,12 +133,107 @@ mod tests {
 
         let params = RevocationParams {
             revocation_endpoint: &endpoint,
+            token_endpoint: "https://logto.dev/oidc/token",
+            client_id: "client_id",
+            token: "token",
+            token_type_hint: Some(TokenTypeHint::RefreshToken),
+            client_auth: &ClientAuth::None,
+        };
+
+        let response = revoke(&client, params).await;
+
+        assert!(response.is_ok());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_oauth_error() {
+        let mut server = mockito::Server::new();
+
+        server
+            .mock("POST", "/oidc/token/revocation")
+            .with_status(401)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error": "invalid_client"}"#)
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token/revocation", server.url());
+
+        let params = RevocationParams {
+            revocation_endpoint: &endpoint,
+            token_endpoint: "https://logto.dev/oidc/token",
             client_id: "client_id",
             token: "token",
+            token_type_hint: None,
+            client_auth: &ClientAuth::ClientSecretPost("wrong_secret".to_string()),
         };
 
         let response = revoke(&client, params).await;
 
-        assert!(response.is_ok())
+        assert_eq!(response.unwrap_err().oauth_error(), Some("invalid_client"));
+    }
+
+    #[tokio::test]
+    async fn should_revoke_all() {
+        let mut server = mockito::Server::new();
+
+        let refresh_token = server
+            .mock("POST", "/oidc/token/revocation")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("token".into(), "refresh_token_value".into()),
+                Matcher::UrlEncoded("token_type_hint".into(), "refresh_token".into()),
+            ]))
+            .with_status(200)
+            .expect(1)
+            .create();
+        let access_token = server
+            .mock("POST", "/oidc/token/revocation")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("token".into(), "access_token_value".into()),
+                Matcher::UrlEncoded("token_type_hint".into(), "access_token".into()),
+            ]))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let mut access_token_map = AccessTokenMap::new();
+        access_token_map.insert(
+            "@https://api.example.com".to_string(),
+            AccessToken {
+                token: "access_token_value".to_string(),
+                scope: Default::default(),
+                expires_at: now() + 3600,
+            },
+        );
+        access_token_map.insert(
+            "@https://expired.example.com".to_string(),
+            AccessToken {
+                token: "expired_access_token_value".to_string(),
+                scope: Default::default(),
+                expires_at: now() - 1,
+            },
+        );
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token/revocation", server.url());
+
+        let response = revoke_all(
+            &client,
+            &mut access_token_map,
+            RevokeAllParameters {
+                revocation_endpoint: &endpoint,
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_id: "client_id",
+                refresh_token: Some("refresh_token_value"),
+                client_auth: &ClientAuth::None,
+            },
+        )
+        .await;
+
+        assert!(response.is_ok());
+        assert!(access_token_map.is_empty());
+        refresh_token.assert();
+        access_token.assert();
     }
 }
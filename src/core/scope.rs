@@ -0,0 +1,205 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A single OAuth scope, covering the reserved and user scopes Logto knows
+/// about. Anything else, such as API resource permissions, is `Custom`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Scope {
+    OpenId,
+    OfflineAccess,
+    Profile,
+    Email,
+    Phone,
+    Address,
+    CustomData,
+    Identities,
+    Roles,
+    Organizations,
+    OrganizationRoles,
+    Custom(String),
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::OpenId => "openid",
+            Self::OfflineAccess => "offline_access",
+            Self::Profile => "profile",
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::Address => "address",
+            Self::CustomData => "custom_data",
+            Self::Identities => "identities",
+            Self::Roles => "roles",
+            Self::Organizations => "urn:logto:scope:organizations",
+            Self::OrganizationRoles => "urn:logto:scope:organization_roles",
+            Self::Custom(scope) => scope,
+        }
+    }
+}
+
+impl From<&str> for Scope {
+    fn from(scope: &str) -> Self {
+        match scope {
+            "openid" => Self::OpenId,
+            "offline_access" => Self::OfflineAccess,
+            "profile" => Self::Profile,
+            "email" => Self::Email,
+            "phone" => Self::Phone,
+            "address" => Self::Address,
+            "custom_data" => Self::CustomData,
+            "identities" => Self::Identities,
+            "roles" => Self::Roles,
+            "urn:logto:scope:organizations" => Self::Organizations,
+            "urn:logto:scope:organization_roles" => Self::OrganizationRoles,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A set of scopes, kept in insertion order and without duplicates.
+/// Parses from and formats to the space-delimited wire form.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scopes(Vec<Scope>);
+
+impl Scopes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, scope: Scope) {
+        if !self.contains(&scope) {
+            self.0.push(scope);
+        }
+    }
+
+    pub fn contains(&self, scope: &Scope) -> bool {
+        self.0.contains(scope)
+    }
+
+    pub fn contains_all(&self, required: &Scopes) -> bool {
+        required.iter().all(|scope| self.contains(scope))
+    }
+
+    /// Scopes in `required` that are not part of this set
+    pub fn missing(&self, required: &Scopes) -> Scopes {
+        required
+            .iter()
+            .filter(|scope| !self.contains(scope))
+            .cloned()
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Scope> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn sort(&mut self) {
+        self.0.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+    }
+}
+
+impl FromStr for Scopes {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.split_whitespace().map(Scope::from).collect())
+    }
+}
+
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scopes: Vec<&str> = self.iter().map(|scope| scope.as_str()).collect();
+        f.write_str(&scopes.join(" "))
+    }
+}
+
+impl FromIterator<Scope> for Scopes {
+    fn from_iter<T: IntoIterator<Item = Scope>>(iter: T) -> Self {
+        let mut scopes = Scopes::new();
+        for scope in iter {
+            scopes.insert(scope);
+        }
+        scopes
+    }
+}
+
+impl From<Vec<Scope>> for Scopes {
+    fn from(scopes: Vec<Scope>) -> Self {
+        scopes.into_iter().collect()
+    }
+}
+
+impl Serialize for Scopes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Scopes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let scopes = String::deserialize(deserializer)?;
+        Ok(scopes.parse().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format_scopes() {
+        let scopes: Scopes =
+            "openid  offline_access urn:logto:scope:organizations read:orders openid"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            scopes,
+            Scopes::from(vec![
+                Scope::OpenId,
+                Scope::OfflineAccess,
+                Scope::Organizations,
+                Scope::Custom("read:orders".to_string()),
+            ])
+        );
+        assert_eq!(
+            scopes.to_string(),
+            "openid offline_access urn:logto:scope:organizations read:orders"
+        );
+    }
+
+    #[test]
+    fn contains_all_scopes() {
+        let granted: Scopes = "openid profile read:orders write:orders".parse().unwrap();
+        let required: Scopes = "read:orders profile".parse().unwrap();
+        let missing: Scopes = "read:orders delete:orders".parse().unwrap();
+
+        assert!(granted.contains_all(&required));
+        assert!(granted.contains_all(&Scopes::new()));
+        assert!(!granted.contains_all(&missing));
+        assert_eq!(
+            granted.missing(&missing),
+            Scopes::from(vec![Scope::Custom("delete:orders".to_string())])
+        );
+    }
+
+    #[test]
+    fn deserialize_scopes_from_string() {
+        let scopes: Scopes = serde_json::from_str(r#""email roles""#).unwrap();
+
+        assert_eq!(scopes, Scopes::from(vec![Scope::Email, Scope::Roles]));
+        assert_eq!(serde_json::to_string(&scopes).unwrap(), r#""email roles""#);
+    }
+}
@@ -0,0 +1,69 @@
+use std::{collections::HashMap, sync::Mutex};
+
+/// Server-side sessions of signed-in users, keyed by the `sid` claim of their
+/// ID token. Logout receivers end sessions through it.
+pub trait SessionStore {
+    fn contains(&self, sid: &str) -> bool;
+
+    /// Ends the session `sid` when given, otherwise every session of `sub`.
+    /// Returns how many sessions ended.
+    fn end_sessions(&self, sid: Option<&str>, sub: Option<&str>) -> usize;
+}
+
+/// Maps each `sid` to its `sub`, for a single server process
+#[derive(Default)]
+pub struct InMemorySessionStore {
+    sessions: Mutex<HashMap<String, String>>,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, sid: &str, sub: &str) {
+        self.sessions().insert(sid.to_string(), sub.to_string());
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.sessions.lock().expect("session store lock poisoned")
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn contains(&self, sid: &str) -> bool {
+        self.sessions().contains_key(sid)
+    }
+
+    fn end_sessions(&self, sid: Option<&str>, sub: Option<&str>) -> usize {
+        let mut sessions = self.sessions();
+        let count = sessions.len();
+
+        sessions.retain(|session_sid, session_sub| match (sid, sub) {
+            (Some(sid), Some(sub)) => session_sid != sid || session_sub != sub,
+            (Some(sid), None) => session_sid != sid,
+            (None, Some(sub)) => session_sub != sub,
+            (None, None) => true,
+        });
+
+        count - sessions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_sessions_by_sid_or_sub() {
+        let store = InMemorySessionStore::new();
+        store.insert("sid1", "user1");
+        store.insert("sid2", "user1");
+        store.insert("sid3", "user2");
+
+        assert_eq!(store.end_sessions(Some("sid1"), Some("user2")), 0);
+        assert_eq!(store.end_sessions(Some("sid1"), None), 1);
+        assert_eq!(store.end_sessions(None, Some("user1")), 1);
+        assert!(store.contains("sid3"));
+    }
+}
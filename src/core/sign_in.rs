@@ -1,72 +1,161 @@
 use reqwest::Url;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+use crate::core::scope::{Scope, Scopes};
+
+#[derive(Debug, Default, Deserialize)]
 pub struct SignInUriGenerationOptions<'a> {
-    authorization_endpoint: String,
-    client_id: &'a str,
-    redirect_uri: &'a str,
-    code_challenge: &'a str,
-    state: &'a str,
-    scopes: Option<Vec<&'a str>>,
-    resources: Option<Vec<&'a str>>,
-    prompt: Option<&'a str>,
-    interaction_mode: Option<&'a str>,
+    pub authorization_endpoint: String,
+    pub client_id: &'a str,
+    pub redirect_uri: &'a str,
+    pub code_challenge: &'a str,
+    pub state: &'a str,
+    pub scopes: Option<Scopes>,
+    pub resources: Option<Vec<&'a str>>,
+    pub prompt: Option<Vec<Prompt>>,
+    pub interaction_mode: Option<InteractionMode>,
+    pub response_mode: Option<ResponseMode>,
+    pub login_hint: Option<&'a str>,
+    pub first_screen: Option<FirstScreen>,
+    pub identifiers: Option<Vec<Identifier>>,
+    pub direct_sign_in: Option<DirectSignIn<'a>>,
+    pub organization_id: Option<&'a str>,
+    pub ui_locales: Option<Vec<&'a str>>,
+    pub max_age: Option<u64>,
+    pub acr_values: Option<Vec<&'a str>>,
+    pub extra_params: Option<Vec<(&'a str, &'a str)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Prompt {
+    Login,
+    Consent,
+    None,
+}
+
+impl Prompt {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::Consent => "consent",
+            Self::None => "none",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InteractionMode {
+    SignIn,
+    SignUp,
+}
+
+impl InteractionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SignIn => "signIn",
+            Self::SignUp => "signUp",
+        }
+    }
 }
 
-enum ReservedScopes {
-    OfflineAccess,
-    OpenId,
+/// The first screen shown by the Logto sign-in experience
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirstScreen {
+    SignIn,
+    Register,
+    ResetPassword,
+    #[serde(rename = "identifier:sign_in")]
+    IdentifierSignIn,
+    #[serde(rename = "identifier:register")]
+    IdentifierRegister,
+    SingleSignOn,
+}
+
+impl FirstScreen {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SignIn => "sign_in",
+            Self::Register => "register",
+            Self::ResetPassword => "reset_password",
+            Self::IdentifierSignIn => "identifier:sign_in",
+            Self::IdentifierRegister => "identifier:register",
+            Self::SingleSignOn => "single_sign_on",
+        }
+    }
 }
 
-enum UserScopes {
-    CustomData,
+/// Identifier types accepted by the `identifier:sign_in` and
+/// `identifier:register` first screens
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Identifier {
     Email,
-    Identities,
     Phone,
-    Profile,
+    Username,
 }
 
-impl UserScopes {
+impl Identifier {
     pub fn as_str(&self) -> &'static str {
         match self {
-            Self::CustomData => "custom_data",
             Self::Email => "email",
-            Self::Identities => "identities",
             Self::Phone => "phone",
-            Self::Profile => "profile",
+            Self::Username => "username",
         }
     }
 }
 
-impl ReservedScopes {
-    pub fn as_str(&self) -> &'static str {
+/// Skips the Logto sign-in experience and goes straight to a social or
+/// enterprise SSO connector
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DirectSignIn<'a> {
+    Social(&'a str),
+    Sso(&'a str),
+}
+
+impl DirectSignIn<'_> {
+    pub fn to_param(&self) -> String {
         match self {
-            Self::OfflineAccess => "offline_access",
-            Self::OpenId => "openid",
+            Self::Social(target) => format!("social:{}", target),
+            Self::Sso(connector_id) => format!("sso:{}", connector_id),
         }
     }
 }
 
-fn with_default_scopes(mut scopes: Option<Vec<&str>>) -> Vec<&str> {
-    let default_scopes: Vec<&str> = vec![
-        ReservedScopes::OfflineAccess.as_str(),
-        ReservedScopes::OpenId.as_str(),
-        UserScopes::Profile.as_str(),
-    ];
-
-    match scopes {
-        None => default_scopes,
-        Some(mut scopes) => {
-            scopes.extend(default_scopes);
-            scopes.sort_unstable();
-            scopes.dedup();
+/// How the authorization response is delivered to the redirect URI,
+/// see https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseMode {
+    Query,
+    Fragment,
+    FormPost,
+}
 
-            scopes
+impl ResponseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Fragment => "fragment",
+            Self::FormPost => "form_post",
         }
     }
 }
 
+fn with_default_scopes(scopes: Option<Scopes>) -> Scopes {
+    let mut scopes = scopes.unwrap_or_default();
+
+    for scope in [Scope::OfflineAccess, Scope::OpenId, Scope::Profile] {
+        scopes.insert(scope);
+    }
+    scopes.sort();
+
+    scopes
+}
+
 const CODE_CHALLENGE_METHOD: &str = "S256";
 const RESPONSE_TYPE: &str = "code";
 
@@ -76,17 +165,35 @@ pub fn generate_signin_uri(
     let mut url = Url::parse(&options.authorization_endpoint)?;
 
     url.query_pairs_mut()
-        .append_pair("client_id", &options.client_id)
-        .append_pair("redirect_uri", &options.redirect_uri)
-        .append_pair("code_challenge", &options.code_challenge)
-        .append_pair("code_challenge_method", CODE_CHALLENGE_METHOD)
-        .append_pair("state", &options.state)
-        .append_pair("response_type", RESPONSE_TYPE)
-        .append_pair(
-            "scope",
-            with_default_scopes(options.scopes).join(" ").as_str(),
-        )
-        .append_pair("prompt", &options.prompt.unwrap_or("consent"));
+        .extend_pairs(build_signin_params(options));
+
+    Ok(url.to_string())
+}
+
+/// Builds the authorization request parameters, shared by the sign-in URI and
+/// pushed authorization requests
+pub(crate) fn build_signin_params<'a>(
+    options: SignInUriGenerationOptions<'a>,
+) -> Vec<(&'a str, String)> {
+    let mut params = vec![
+        ("client_id", options.client_id.to_string()),
+        ("redirect_uri", options.redirect_uri.to_string()),
+        ("code_challenge", options.code_challenge.to_string()),
+        ("code_challenge_method", CODE_CHALLENGE_METHOD.to_string()),
+        ("state", options.state.to_string()),
+        ("response_type", RESPONSE_TYPE.to_string()),
+        ("scope", with_default_scopes(options.scopes).to_string()),
+        (
+            "prompt",
+            options
+                .prompt
+                .unwrap_or_else(|| vec![Prompt::Consent])
+                .iter()
+                .map(|prompt| prompt.as_str())
+                .collect::<Vec<&str>>()
+                .join(" "),
+        ),
+    ];
 
     let mut resources = Vec::<&str>::new();
     if let Some(resources_list) = options.resources {
@@ -95,16 +202,61 @@ pub fn generate_signin_uri(
                 resources.push(resource);
             }
         }
-        url.query_pairs_mut()
-            .append_pair("resource", resources.join(" ").as_str());
+        params.push(("resource", resources.join(" ")));
     }
 
     if let Some(interaction_mode) = options.interaction_mode {
-        url.query_pairs_mut()
-            .append_pair("interaction_mode", &interaction_mode);
+        params.push(("interaction_mode", interaction_mode.as_str().to_string()));
     }
 
-    Ok(url.to_string())
+    if let Some(response_mode) = options.response_mode {
+        params.push(("response_mode", response_mode.as_str().to_string()));
+    }
+
+    if let Some(login_hint) = options.login_hint {
+        params.push(("login_hint", login_hint.to_string()));
+    }
+
+    if let Some(first_screen) = options.first_screen {
+        params.push(("first_screen", first_screen.as_str().to_string()));
+    }
+
+    if let Some(identifiers) = options.identifiers {
+        let identifiers: Vec<&str> = identifiers.iter().map(|i| i.as_str()).collect();
+        params.push(("identifier", identifiers.join(" ")));
+    }
+
+    if let Some(direct_sign_in) = options.direct_sign_in {
+        params.push(("direct_sign_in", direct_sign_in.to_param()));
+    }
+
+    if let Some(organization_id) = options.organization_id {
+        params.push(("organization_id", organization_id.to_string()));
+    }
+
+    if let Some(ui_locales) = options.ui_locales {
+        params.push(("ui_locales", ui_locales.join(" ")));
+    }
+
+    if let Some(max_age) = options.max_age {
+        params.push(("max_age", max_age.to_string()));
+    }
+
+    if let Some(acr_values) = options.acr_values {
+        params.push(("acr_values", acr_values.join(" ")));
+    }
+
+    // Extra parameters can't override the ones set above, e.g. a second
+    // `state` or `redirect_uri`
+    if let Some(extra_params) = options.extra_params {
+        for (key, value) in extra_params {
+            if !params.iter().any(|(set, _)| *set == key) {
+                params.push((key, value.to_string()));
+            }
+        }
+    }
+
+    params
 }
 
 #[cfg(test)]
@@ -125,6 +277,8 @@ mod tests {
             resources: None,
             prompt: None,
             interaction_mode: None,
+            response_mode: None,
+            ..Default::default()
         });
 
         if let Ok(uri) = generated_uri {
@@ -164,10 +318,12 @@ mod tests {
             redirect_uri: "https://example.com/callback",
             code_challenge: "codeChallenge",
             state: "state",
-            scopes: Some(vec![UserScopes::Email.as_str()]),
+            scopes: Some(Scopes::from(vec![Scope::Email])),
             resources: Some(vec!["resource1", "resource2"]),
-            prompt: Some("login"),
+            prompt: Some(vec![Prompt::Login]),
             interaction_mode: None,
+            response_mode: None,
+            ..Default::default()
         });
 
         if let Ok(uri) = generated_uri {
@@ -211,7 +367,9 @@ mod tests {
             scopes: None,
             resources: None,
             prompt: None,
-            interaction_mode: Some("signUp"),
+            interaction_mode: Some(InteractionMode::SignUp),
+            response_mode: None,
+            ..Default::default()
         });
 
         if let Ok(uri) = generated_uri {
@@ -243,4 +401,161 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_generate_signin_uri_with_response_mode() {
+        let generated_uri = generate_signin_uri(SignInUriGenerationOptions {
+            authorization_endpoint: "http://logto.dev/oidc/sign-in".to_string(),
+            client_id: "clientId",
+            redirect_uri: "https://example.com/callback",
+            code_challenge: "codeChallenge",
+            state: "state",
+            scopes: None,
+            resources: None,
+            prompt: None,
+            interaction_mode: None,
+            response_mode: Some(ResponseMode::FormPost),
+            ..Default::default()
+        });
+
+        if let Ok(uri) = generated_uri {
+            let url = Url::parse(uri.as_str());
+            if let Ok(parsed_url) = url {
+                let raw_params: HashMap<String, String> =
+                    parsed_url.query_pairs().into_owned().collect();
+
+                let params: HashMap<&str, &str> = raw_params
+                    .iter()
+                    .map(|(a, b)| (a.as_str(), b.as_str()))
+                    .collect();
+
+                let expected_params: HashMap<&str, &str> = [
+                    ("client_id", "clientId"),
+                    ("redirect_uri", "https://example.com/callback"),
+                    ("code_challenge", "codeChallenge"),
+                    ("code_challenge_method", "S256"),
+                    ("response_type", "code"),
+                    ("state", "state"),
+                    ("scope", "offline_access openid profile"),
+                    ("prompt", "consent"),
+                    ("response_mode", "form_post"),
+                ]
+                .into_iter()
+                .collect();
+
+                assert_eq!(params, expected_params)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_signin_uri_with_logto_params() {
+        let generated_uri = generate_signin_uri(SignInUriGenerationOptions {
+            authorization_endpoint: "http://logto.dev/oidc/sign-in".to_string(),
+            client_id: "clientId",
+            redirect_uri: "https://example.com/callback",
+            code_challenge: "codeChallenge",
+            state: "state",
+            prompt: Some(vec![Prompt::Login, Prompt::Consent]),
+            login_hint: Some("user@example.com"),
+            first_screen: Some(FirstScreen::IdentifierSignIn),
+            identifiers: Some(vec![Identifier::Email, Identifier::Phone]),
+            direct_sign_in: Some(DirectSignIn::Social("google")),
+            organization_id: Some("organizationId"),
+            ui_locales: Some(vec!["fr-CA", "en"]),
+            max_age: Some(3600),
+            acr_values: Some(vec!["urn:mace:incommon:iap:silver"]),
+            extra_params: Some(vec![("foo", "bar")]),
+            ..Default::default()
+        });
+
+        if let Ok(uri) = generated_uri {
+            let url = Url::parse(uri.as_str());
+            if let Ok(parsed_url) = url {
+                let raw_params: HashMap<String, String> =
+                    parsed_url.query_pairs().into_owned().collect();
+
+                let params: HashMap<&str, &str> = raw_params
+                    .iter()
+                    .map(|(a, b)| (a.as_str(), b.as_str()))
+                    .collect();
+
+                let expected_params: HashMap<&str, &str> = [
+                    ("client_id", "clientId"),
+                    ("redirect_uri", "https://example.com/callback"),
+                    ("code_challenge", "codeChallenge"),
+                    ("code_challenge_method", "S256"),
+                    ("response_type", "code"),
+                    ("state", "state"),
+                    ("scope", "offline_access openid profile"),
+                    ("prompt", "login consent"),
+                    ("login_hint", "user@example.com"),
+                    ("first_screen", "identifier:sign_in"),
+                    ("identifier", "email phone"),
+                    ("direct_sign_in", "social:google"),
+                    ("organization_id", "organizationId"),
+                    ("ui_locales", "fr-CA en"),
+                    ("max_age", "3600"),
+                    ("acr_values", "urn:mace:incommon:iap:silver"),
+                    ("foo", "bar"),
+                ]
+                .into_iter()
+                .collect();
+
+                assert_eq!(params, expected_params)
+            } else {
+                panic!("Generated URI is not a valid URL")
+            }
+        } else {
+            panic!("Failed to generate sign-in URI")
+        }
+    }
+
+    #[test]
+    fn test_extra_params_dont_override() {
+        let params = build_signin_params(SignInUriGenerationOptions {
+            authorization_endpoint: "http://logto.dev/oidc/sign-in".to_string(),
+            client_id: "clientId",
+            redirect_uri: "https://example.com/callback",
+            code_challenge: "codeChallenge",
+            state: "state",
+            extra_params: Some(vec![
+                ("state", "other"),
+                ("redirect_uri", "https://evil.example.com"),
+                ("client_id", "otherClientId"),
+                ("foo", "bar"),
+            ]),
+            ..Default::default()
+        });
+
+        let values = |key: &str| -> Vec<&str> {
+            params
+                .iter()
+                .filter(|(k, _)| *k == key)
+                .map(|(_, v)| v.as_str())
+                .collect()
+        };
+
+        assert_eq!(values("state"), vec!["state"]);
+        assert_eq!(values("redirect_uri"), vec!["https://example.com/callback"]);
+        assert_eq!(values("client_id"), vec!["clientId"]);
+        assert_eq!(values("foo"), vec!["bar"]);
+    }
+
+    #[test]
+    fn test_deserialize_first_screen() {
+        let first_screens: Vec<FirstScreen> = serde_json::from_str(
+            r#"["identifier:sign_in", "identifier:register", "reset_password"]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            first_screens,
+            vec![
+                FirstScreen::IdentifierSignIn,
+                FirstScreen::IdentifierRegister,
+                FirstScreen::ResetPassword,
+            ]
+        );
+    }
 }
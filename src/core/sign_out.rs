@@ -1,11 +1,36 @@
-use reqwest::Url;
+use reqwest::{Client, Url};
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+use crate::core::{
+    access_token::AccessTokenMap,
+    client_auth::ClientAuth,
+    error::LogtoError,
+    revoke::{revoke_all, RevokeAllParameters},
+};
+
+/// RP-initiated logout request parameters,
+/// see https://openid.net/specs/openid-connect-rpinitiated-1_0.html#RPLogout
+#[derive(Debug, Default, Deserialize)]
 pub struct SignOutUriGenerationOptions {
-    end_session_endpoint: String,
-    client_id: String, // Docs convention says id_token, but other SDKs use client_id
-    post_logout_redirect_uri: Option<String>,
+    pub end_session_endpoint: String,
+    pub client_id: String, // Docs convention says id_token, but other SDKs use client_id
+    pub post_logout_redirect_uri: Option<String>,
+    pub id_token_hint: Option<String>,
+    /// Echoed back on the post-logout redirect, check it with `verify_sign_out_state`
+    pub state: Option<String>,
+    pub logout_hint: Option<String>,
+    pub ui_locales: Option<Vec<String>>,
+}
+
+pub struct SignOutParameters<'a> {
+    pub revocation_endpoint: &'a str,
+    /// Audience of client assertions
+    pub token_endpoint: &'a str,
+    pub client_id: &'a str,
+    /// Taken from the caller's storage, it can't be used after sign-out
+    pub refresh_token: Option<String>,
+    pub client_auth: &'a ClientAuth,
+    pub uri_options: SignOutUriGenerationOptions,
 }
 
 pub fn generate_signout_uri(
@@ -21,9 +46,63 @@ pub fn generate_signout_uri(
             .append_pair("post_logout_redirect_uri", redirect_uri);
     }
 
+    if let Some(id_token_hint) = &options.id_token_hint {
+        url.query_pairs_mut()
+            .append_pair("id_token_hint", id_token_hint);
+    }
+
+    if let Some(state) = &options.state {
+        url.query_pairs_mut().append_pair("state", state);
+    }
+
+    if let Some(logout_hint) = &options.logout_hint {
+        url.query_pairs_mut()
+            .append_pair("logout_hint", logout_hint);
+    }
+
+    if let Some(ui_locales) = &options.ui_locales {
+        url.query_pairs_mut()
+            .append_pair("ui_locales", &ui_locales.join(" "));
+    }
+
     Ok(url.as_str().to_owned())
 }
 
+/// Revokes the refresh token and cached access tokens, clears the map and
+/// returns the end-session URI to redirect to. Like the other Logto SDKs,
+/// revocation errors don't stop the sign-out.
+pub async fn sign_out<'a>(
+    client: &Client,
+    access_token_map: &mut AccessTokenMap,
+    parameters: SignOutParameters<'a>,
+) -> Result<String, LogtoError> {
+    let _ = revoke_all(
+        client,
+        access_token_map,
+        RevokeAllParameters {
+            revocation_endpoint: parameters.revocation_endpoint,
+            token_endpoint: parameters.token_endpoint,
+            client_id: parameters.client_id,
+            refresh_token: parameters.refresh_token.as_deref(),
+            client_auth: parameters.client_auth,
+        },
+    )
+    .await;
+
+    generate_signout_uri(parameters.uri_options).map_err(|e| LogtoError::InvalidUrl(e.to_string()))
+}
+
+/// Checks the `state` on the post-logout redirect against the one sent
+pub fn verify_sign_out_state(callback_uri: &str, state: &str) -> Result<(), LogtoError> {
+    let url = Url::parse(callback_uri).map_err(|e| LogtoError::InvalidUrl(e.to_string()))?;
+
+    match url.query_pairs().find(|(key, _)| key == "state") {
+        Some((_, value)) if value == state => Ok(()),
+        Some(_) => Err(LogtoError::InvalidCallback("state mismatch".to_string())),
+        None => Err(LogtoError::InvalidCallback("missing state".to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,6 +113,7 @@ mod tests {
             end_session_endpoint: "http://logto.dev/oidc/session/end".to_string(),
             client_id: "clientId".to_string(),
             post_logout_redirect_uri: None,
+            ..Default::default()
         });
 
         if let Ok(uri) = generated_uri {
@@ -47,6 +127,7 @@ mod tests {
             end_session_endpoint: "http://logto.dev/oidc/session/end".to_string(),
             client_id: "clientId".to_string(),
             post_logout_redirect_uri: Some("http://example.com/callback".to_string()),
+            ..Default::default()
         });
 
         if let Ok(uri) = generated_uri {
@@ -56,4 +137,68 @@ mod tests {
             )
         }
     }
+
+    #[tokio::test]
+    async fn test_generate_signout_uri_with_hints() {
+        let generated_uri = generate_signout_uri(SignOutUriGenerationOptions {
+            end_session_endpoint: "http://logto.dev/oidc/session/end".to_string(),
+            client_id: "clientId".to_string(),
+            id_token_hint: Some("idToken".to_string()),
+            state: Some("state".to_string()),
+            logout_hint: Some("user@example.com".to_string()),
+            ui_locales: Some(vec!["fr-CA".to_string(), "en".to_string()]),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            generated_uri.unwrap(),
+            "http://logto.dev/oidc/session/end?client_id=clientId&id_token_hint=idToken&state=state&logout_hint=user%40example.com&ui_locales=fr-CA+en"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sign_out() {
+        let mut server = mockito::Server::new();
+
+        let mock = server
+            .mock("POST", "/oidc/token/revocation")
+            .with_status(503)
+            .expect(1)
+            .create();
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/oidc/token/revocation", server.url());
+        let mut access_token_map = AccessTokenMap::new();
+
+        let uri = sign_out(
+            &client,
+            &mut access_token_map,
+            SignOutParameters {
+                revocation_endpoint: &endpoint,
+                token_endpoint: "https://logto.dev/oidc/token",
+                client_id: "clientId",
+                refresh_token: Some("refreshToken".to_string()),
+                client_auth: &ClientAuth::None,
+                uri_options: SignOutUriGenerationOptions {
+                    end_session_endpoint: "http://logto.dev/oidc/session/end".to_string(),
+                    client_id: "clientId".to_string(),
+                    ..Default::default()
+                },
+            },
+        )
+        .await;
+
+        assert_eq!(
+            uri.unwrap(),
+            "http://logto.dev/oidc/session/end?client_id=clientId"
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn test_verify_sign_out_state() {
+        assert!(verify_sign_out_state("http://example.com/callback?state=state", "state").is_ok());
+        assert!(verify_sign_out_state("http://example.com/callback?state=other", "state").is_err());
+        assert!(verify_sign_out_state("http://example.com/callback", "state").is_err());
+    }
 }
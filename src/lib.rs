@@ -1,2 +1,3 @@
-mod core;
-mod utils;
+pub mod core;
+pub mod management;
+pub mod utils;
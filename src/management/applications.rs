@@ -0,0 +1,452 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    core::error::LogtoError,
+    management::{
+        client::{ManagementClient, Page, Pagination},
+        roles::Role,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApplicationType {
+    Native,
+    #[serde(rename = "SPA")]
+    Spa,
+    Traditional,
+    MachineToMachine,
+    Protected,
+    #[serde(rename = "SAML")]
+    Saml,
+}
+
+impl ApplicationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Native => "Native",
+            Self::Spa => "SPA",
+            Self::Traditional => "Traditional",
+            Self::MachineToMachine => "MachineToMachine",
+            Self::Protected => "Protected",
+            Self::Saml => "SAML",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcClientMetadata {
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[serde(default)]
+    pub post_logout_redirect_uris: Vec<String>,
+}
+
+/// See https://openapi.logto.io/group/endpoint-applications
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Application {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub application_type: ApplicationType,
+    /// The legacy secret, prefer `list_application_secrets`
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub oidc_client_metadata: OidcClientMetadata,
+    #[serde(default)]
+    pub custom_client_metadata: Map<String, Value>,
+    #[serde(default)]
+    pub is_third_party: bool,
+    pub created_at: u64,
+}
+
+/// A named client secret, an application can have several during rotation
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationSecret {
+    pub application_id: String,
+    pub name: String,
+    pub value: String,
+    /// Unix timestamps in milliseconds
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct ListApplicationsQuery<'a> {
+    /// Any of the types, all if empty
+    pub types: Vec<ApplicationType>,
+    /// Fuzzy search over the id, name and description
+    pub search: Option<&'a str>,
+    pub pagination: Option<Pagination>,
+}
+
+impl<'a> ListApplicationsQuery<'a> {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query: Vec<_> = self
+            .types
+            .iter()
+            .map(|application_type| ("types", application_type.as_str().to_string()))
+            .collect();
+
+        if let Some(search) = self.search {
+            query.push(("search", format!("%{}%", search)));
+        }
+
+        if let Some(pagination) = self.pagination {
+            query.extend(pagination.to_query());
+        }
+
+        query
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApplication<'a> {
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub application_type: ApplicationType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oidc_client_metadata: Option<OidcClientMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_client_metadata: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_third_party: Option<bool>,
+}
+
+/// Only the set fields are updated
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApplication<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    /// Replaces both URI lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oidc_client_metadata: Option<OidcClientMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_client_metadata: Option<Map<String, Value>>,
+}
+
+impl ManagementClient {
+    pub async fn list_applications(
+        &self,
+        query: ListApplicationsQuery<'_>,
+    ) -> Result<Page<Application>, LogtoError> {
+        let request = self.request(Method::GET, &["applications"]).await?;
+
+        self.send_page(request.query(&query.to_query())).await
+    }
+
+    pub async fn get_application(&self, application_id: &str) -> Result<Application, LogtoError> {
+        let request = self
+            .request(Method::GET, &["applications", application_id])
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_application(
+        &self,
+        application: CreateApplication<'_>,
+    ) -> Result<Application, LogtoError> {
+        let request = self.request(Method::POST, &["applications"]).await?;
+
+        self.send(request.json(&application)).await
+    }
+
+    pub async fn update_application(
+        &self,
+        application_id: &str,
+        application: UpdateApplication<'_>,
+    ) -> Result<Application, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["applications", application_id])
+            .await?;
+
+        self.send(request.json(&application)).await
+    }
+
+    /// Replaces the sign-in and post sign-out redirect URIs
+    pub async fn set_application_redirect_uris(
+        &self,
+        application_id: &str,
+        redirect_uris: Vec<String>,
+        post_logout_redirect_uris: Vec<String>,
+    ) -> Result<Application, LogtoError> {
+        self.update_application(
+            application_id,
+            UpdateApplication {
+                oidc_client_metadata: Some(OidcClientMetadata {
+                    redirect_uris,
+                    post_logout_redirect_uris,
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn delete_application(&self, application_id: &str) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["applications", application_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_application_secrets(
+        &self,
+        application_id: &str,
+    ) -> Result<Vec<ApplicationSecret>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["applications", application_id, "secrets"])
+            .await?;
+
+        self.send(request).await
+    }
+
+    /// `expires_at` is a Unix timestamp in milliseconds, the secret never
+    /// expires if `None`
+    pub async fn create_application_secret(
+        &self,
+        application_id: &str,
+        name: &str,
+        expires_at: Option<u64>,
+    ) -> Result<ApplicationSecret, LogtoError> {
+        let request = self
+            .request(Method::POST, &["applications", application_id, "secrets"])
+            .await?;
+
+        let mut body = json!({ "name": name });
+        if let Some(expires_at) = expires_at {
+            body["expiresAt"] = expires_at.into();
+        }
+
+        self.send(request.json(&body)).await
+    }
+
+    pub async fn delete_application_secret(
+        &self,
+        application_id: &str,
+        name: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["applications", application_id, "secrets", name],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    /// Creates the secret `new_name` then deletes `old_name`. To keep both
+    /// valid while clients are updated, call the two steps separately.
+    pub async fn rotate_application_secret(
+        &self,
+        application_id: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<ApplicationSecret, LogtoError> {
+        let secret = self
+            .create_application_secret(application_id, new_name, None)
+            .await?;
+
+        self.delete_application_secret(application_id, old_name)
+            .await?;
+
+        Ok(secret)
+    }
+
+    pub async fn list_application_roles(
+        &self,
+        application_id: &str,
+    ) -> Result<Vec<Role>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["applications", application_id, "roles"])
+            .await?;
+
+        self.send(request).await
+    }
+
+    /// Assigns machine-to-machine roles, the application must be
+    /// `ApplicationType::MachineToMachine`
+    pub async fn assign_application_roles(
+        &self,
+        application_id: &str,
+        role_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::POST, &["applications", application_id, "roles"])
+            .await?;
+
+        self.send_empty(request.json(&json!({ "roleIds": role_ids })))
+            .await
+    }
+
+    pub async fn remove_application_role(
+        &self,
+        application_id: &str,
+        role_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["applications", application_id, "roles", role_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const APPLICATION: &str = r#"{
+        "id": "application_id",
+        "tenantId": "default",
+        "name": "Acme dashboard",
+        "description": null,
+        "type": "SPA",
+        "secret": "internal",
+        "oidcClientMetadata": {
+            "redirectUris": ["https://acme.example.com/callback"],
+            "postLogoutRedirectUris": []
+        },
+        "customClientMetadata": {},
+        "isThirdParty": false,
+        "createdAt": 1700000000000
+    }"#;
+
+    #[tokio::test]
+    async fn test_list_applications() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/applications")
+            .match_header("authorization", "Bearer management_token")
+            .match_query(Matcher::Exact("types=SPA&types=Native".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("total-number", "1")
+            .with_body(format!("[{}]", APPLICATION))
+            .create();
+
+        let page = client
+            .list_applications(ListApplicationsQuery {
+                types: vec![ApplicationType::Spa, ApplicationType::Native],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(page.items[0].application_type, ApplicationType::Spa);
+        assert_eq!(
+            page.items[0].oidc_client_metadata.redirect_uris,
+            vec!["https://acme.example.com/callback"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_application_redirect_uris() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let mock = server
+            .mock("PATCH", "/api/applications/application_id")
+            .match_body(Matcher::Json(json!({
+                "oidcClientMetadata": {
+                    "redirectUris": ["https://acme.example.com/callback"],
+                    "postLogoutRedirectUris": ["https://acme.example.com"],
+                },
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(APPLICATION)
+            .expect(1)
+            .create();
+
+        client
+            .set_application_redirect_uris(
+                "application_id",
+                vec!["https://acme.example.com/callback".to_string()],
+                vec!["https://acme.example.com".to_string()],
+            )
+            .await
+            .unwrap();
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_rotate_application_secret() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let create = server
+            .mock("POST", "/api/applications/application_id/secrets")
+            .match_body(Matcher::Json(json!({ "name": "2024-06" })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "tenantId": "default",
+                    "applicationId": "application_id",
+                    "name": "2024-06",
+                    "value": "new_secret",
+                    "createdAt": 1700000000000,
+                    "expiresAt": null
+                }"#,
+            )
+            .expect(1)
+            .create();
+        let delete = server
+            .mock("DELETE", "/api/applications/application_id/secrets/2024-01")
+            .with_status(204)
+            .expect(1)
+            .create();
+
+        let secret = client
+            .rotate_application_secret("application_id", "2024-01", "2024-06")
+            .await
+            .unwrap();
+
+        assert_eq!(secret.value, "new_secret");
+        create.assert();
+        delete.assert();
+    }
+
+    #[tokio::test]
+    async fn test_assign_application_roles() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let mock = server
+            .mock("POST", "/api/applications/application_id/roles")
+            .match_body(Matcher::Json(json!({ "roleIds": ["role_id"] })))
+            .with_status(201)
+            .expect(1)
+            .create();
+
+        client
+            .assign_application_roles("application_id", &["role_id"])
+            .await
+            .unwrap();
+        mock.assert();
+    }
+}
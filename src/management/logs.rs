@@ -0,0 +1,433 @@
+use std::{collections::VecDeque, time::Duration};
+
+use futures::{stream, Stream};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    core::error::LogtoError,
+    management::client::{ManagementClient, Page, Pagination},
+};
+
+const PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum LogResult {
+    Success,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPayload {
+    pub result: LogResult,
+    pub user_id: Option<String>,
+    pub application_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Event-specific fields, e.g. `error` or `params`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// See https://openapi.logto.io/group/endpoint-logs
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    pub id: String,
+    /// e.g. `ExchangeTokenBy.AuthorizationCode`
+    pub key: String,
+    pub payload: LogPayload,
+    /// Unix timestamp in milliseconds
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub user_id: Option<String>,
+    pub application_id: Option<String>,
+    pub log_key: Option<String>,
+}
+
+impl LogFilter {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+
+        if let Some(user_id) = &self.user_id {
+            query.push(("userId", user_id.clone()));
+        }
+
+        if let Some(application_id) = &self.application_id {
+            query.push(("applicationId", application_id.clone()));
+        }
+
+        if let Some(log_key) = &self.log_key {
+            query.push(("logKey", log_key.clone()));
+        }
+
+        query
+    }
+}
+
+/// Position of a log tail, persist it to resume without duplicates. Logs
+/// only have millisecond timestamps, so the ids already seen at the last
+/// timestamp are kept too.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogCursor {
+    pub created_at: u64,
+    pub ids: Vec<String>,
+}
+
+impl LogCursor {
+    /// Skips the logs created before `created_at`, in milliseconds. The
+    /// default cursor replays the whole history.
+    pub fn starting_at(created_at: u64) -> Self {
+        Self {
+            created_at,
+            ids: Vec::new(),
+        }
+    }
+
+    fn is_seen(&self, log: &Log) -> bool {
+        log.created_at < self.created_at
+            || (log.created_at == self.created_at && self.ids.contains(&log.id))
+    }
+
+    fn advance(&mut self, log: &Log) {
+        if log.created_at > self.created_at {
+            self.created_at = log.created_at;
+            self.ids.clear();
+        }
+
+        self.ids.push(log.id.clone());
+    }
+}
+
+struct TailState {
+    cursor: LogCursor,
+    pending: VecDeque<Log>,
+    /// Page where the last batch was found, the next one is near it
+    page: u32,
+    is_caught_up: bool,
+}
+
+impl ManagementClient {
+    /// Lists logs, newest first
+    pub async fn list_logs(
+        &self,
+        filter: &LogFilter,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<Log>, LogtoError> {
+        let request = self.request(Method::GET, &["logs"]).await?;
+
+        let mut query = filter.to_query();
+        query.extend(pagination.map(Pagination::to_query).unwrap_or_default());
+
+        self.send_page(request.query(&query)).await
+    }
+
+    pub async fn get_log(&self, log_id: &str) -> Result<Log, LogtoError> {
+        let request = self.request(Method::GET, &["logs", log_id]).await?;
+
+        self.send(request).await
+    }
+
+    /// The oldest logs not seen by `cursor`, oldest first, at most a page.
+    /// Pages are newest first and shift as logs are added, so this looks for
+    /// the page where the unseen logs meet the seen ones, starting from
+    /// `page`. Empty once caught up.
+    async fn next_logs(
+        &self,
+        filter: &LogFilter,
+        cursor: &LogCursor,
+        page: &mut u32,
+    ) -> Result<Vec<Log>, LogtoError> {
+        // A page of unseen logs only, yielded if the next one has none
+        let mut newer: Option<(u32, Vec<Log>)> = None;
+
+        loop {
+            let batch = self
+                .list_logs(
+                    filter,
+                    Some(Pagination {
+                        page: *page,
+                        page_size: PAGE_SIZE,
+                    }),
+                )
+                .await?
+                .items;
+            let is_last = batch.len() < PAGE_SIZE as usize;
+            let has_seen = batch.iter().any(|log| cursor.is_seen(log));
+            let mut unseen: Vec<Log> = batch
+                .into_iter()
+                .filter(|log| !cursor.is_seen(log))
+                .collect();
+
+            if unseen.is_empty() {
+                if let Some((newer_page, mut logs)) = newer {
+                    *page = newer_page;
+                    logs.reverse();
+                    return Ok(logs);
+                }
+
+                if *page == 1 {
+                    return Ok(unseen);
+                }
+
+                *page -= 1;
+                continue;
+            }
+
+            if has_seen || is_last {
+                unseen.reverse();
+                return Ok(unseen);
+            }
+
+            newer = Some((*page, unseen));
+            *page += 1;
+        }
+    }
+
+    /// Streams the logs matching `filter` created after `cursor`, oldest
+    /// first, then polls for new ones every `poll_interval`. Each log comes
+    /// with the cursor to persist once it's handled. Errors are yielded and
+    /// polling goes on. A backlog is fetched a page at a time, oldest first,
+    /// without gaps or buffering it whole.
+    pub fn tail_logs(
+        &self,
+        filter: LogFilter,
+        cursor: LogCursor,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<(Log, LogCursor), LogtoError>> + '_ {
+        let state = TailState {
+            cursor,
+            pending: VecDeque::new(),
+            page: 1,
+            is_caught_up: false,
+        };
+
+        stream::unfold(state, move |mut state| {
+            let filter = filter.clone();
+
+            async move {
+                loop {
+                    if let Some(log) = state.pending.pop_front() {
+                        state.cursor.advance(&log);
+                        let cursor = state.cursor.clone();
+
+                        return Some((Ok((log, cursor)), state));
+                    }
+
+                    if state.is_caught_up {
+                        tokio::time::sleep(poll_interval).await;
+                    }
+
+                    match self
+                        .next_logs(&filter, &state.cursor, &mut state.page)
+                        .await
+                    {
+                        Ok(logs) => {
+                            state.is_caught_up = logs.is_empty();
+                            state.pending.extend(logs);
+                        }
+                        Err(e) => {
+                            // Waits before retrying
+                            state.is_caught_up = true;
+                            return Some((Err(e), state));
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    fn log(id: &str, created_at: u64) -> Value {
+        json!({
+            "id": id,
+            "tenantId": "default",
+            "key": "ExchangeTokenBy.AuthorizationCode",
+            "payload": {
+                "key": "ExchangeTokenBy.AuthorizationCode",
+                "result": "Success",
+                "userId": "user_id",
+                "applicationId": "application_id",
+                "ip": "127.0.0.1",
+            },
+            "createdAt": created_at,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_logs() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/logs")
+            .match_header("authorization", "Bearer management_token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("userId".into(), "user_id".into()),
+                Matcher::UrlEncoded("logKey".into(), "ExchangeTokenBy.AuthorizationCode".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("total-number", "1")
+            .with_body(json!([log("log_id", 1700000000000)]).to_string())
+            .create();
+
+        let page = client
+            .list_logs(
+                &LogFilter {
+                    user_id: Some("user_id".to_string()),
+                    log_key: Some("ExchangeTokenBy.AuthorizationCode".to_string()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(page.items[0].payload.result, LogResult::Success);
+        assert_eq!(page.items[0].payload.extra["key"], page.items[0].key);
+    }
+
+    #[tokio::test]
+    async fn test_tail_logs_from_cursor() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/logs")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!([
+                    log("c", 3000),
+                    log("b2", 2000),
+                    log("b", 2000),
+                    log("a", 1000)
+                ])
+                .to_string(),
+            )
+            .create();
+
+        let cursor = LogCursor {
+            created_at: 2000,
+            ids: vec!["b".to_string()],
+        };
+
+        let tailed: Vec<_> = client
+            .tail_logs(LogFilter::default(), cursor, Duration::from_secs(60))
+            .take(2)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let ids: Vec<_> = tailed.iter().map(|(log, _)| log.id.as_str()).collect();
+        assert_eq!(ids, vec!["b2", "c"]);
+        assert_eq!(
+            tailed[0].1,
+            LogCursor {
+                created_at: 2000,
+                ids: vec!["b".to_string(), "b2".to_string()],
+            }
+        );
+        assert_eq!(
+            tailed[1].1,
+            LogCursor {
+                created_at: 3000,
+                ids: vec!["c".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tail_logs_skips_overlap() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        // Matched once, the next poll falls through to the second page 1
+        server
+            .mock("GET", "/api/logs")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([log("c", 3000), log("b", 2000), log("a", 1000)]).to_string())
+            .expect(1)
+            .create();
+        server
+            .mock("GET", "/api/logs")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([log("d", 4000), log("c", 3000), log("b", 2000)]).to_string())
+            .create();
+
+        let tailed: Vec<_> = client
+            .tail_logs(
+                LogFilter::default(),
+                LogCursor::default(),
+                Duration::from_millis(10),
+            )
+            .take(4)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let ids: Vec<_> = tailed.iter().map(|(log, _)| log.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_tail_logs_backlog_across_pages() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let logs: Vec<_> = (0..150)
+            .rev()
+            .map(|i| log(&i.to_string(), 1000 + i))
+            .collect();
+
+        server
+            .mock("GET", "/api/logs")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!(logs[..100]).to_string())
+            .create();
+        server
+            .mock("GET", "/api/logs")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!(logs[100..]).to_string())
+            .create();
+
+        let tailed: Vec<_> = client
+            .tail_logs(
+                LogFilter::default(),
+                LogCursor::default(),
+                Duration::from_secs(60),
+            )
+            .take(150)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let ids: Vec<_> = tailed.iter().map(|(log, _)| log.id.clone()).collect();
+        let expected: Vec<_> = (0..150).map(|i: u64| i.to_string()).collect();
+        assert_eq!(ids, expected);
+    }
+}
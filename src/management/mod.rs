@@ -0,0 +1,8 @@
+pub mod applications;
+pub mod client;
+pub mod logs;
+pub mod organizations;
+pub mod reconcile;
+pub mod resources;
+pub mod roles;
+pub mod users;
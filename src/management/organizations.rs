@@ -0,0 +1,746 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    core::error::LogtoError,
+    management::{
+        client::{ManagementClient, Page, Pagination},
+        users::User,
+    },
+};
+
+/// See https://openapi.logto.io/group/endpoint-organizations
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub custom_data: Map<String, Value>,
+    #[serde(default)]
+    pub is_mfa_required: bool,
+    pub created_at: u64,
+}
+
+/// A role template shared by all organizations,
+/// see https://openapi.logto.io/group/endpoint-organization-roles
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationRole {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A permission of the organization template,
+/// see https://openapi.logto.io/group/endpoint-organization-scopes
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationScope {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Role reference embedded in members and invitations
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OrganizationRoleRef {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationMember {
+    #[serde(flatten)]
+    pub user: User,
+    #[serde(default)]
+    pub organization_roles: Vec<OrganizationRoleRef>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Expired,
+    Revoked,
+}
+
+/// See https://openapi.logto.io/group/endpoint-organization-invitations
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationInvitation {
+    pub id: String,
+    pub inviter_id: Option<String>,
+    /// Email address of the invitee
+    pub invitee: String,
+    pub accepted_user_id: Option<String>,
+    pub organization_id: String,
+    pub status: InvitationStatus,
+    #[serde(default)]
+    pub organization_roles: Vec<OrganizationRoleRef>,
+    /// Unix timestamps in milliseconds
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrganization<'a> {
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_data: Option<Map<String, Value>>,
+}
+
+/// Only the set fields are updated
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOrganization<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    /// Replaces the whole custom data object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_data: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_mfa_required: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrganizationRole<'a> {
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub organization_scope_ids: Vec<&'a str>,
+}
+
+/// Used both to create and update organization roles and scopes
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationTemplateEntry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrganizationInvitation<'a> {
+    pub invitee: &'a str,
+    pub organization_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inviter_id: Option<&'a str>,
+    /// Unix timestamp in milliseconds
+    pub expires_at: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub organization_role_ids: Vec<&'a str>,
+    /// Variables of the invitation email template, no email is sent if `None`
+    #[serde(serialize_with = "serialize_message_payload")]
+    pub message_payload: Option<Map<String, Value>>,
+}
+
+/// Logto expects `false` to skip the invitation email
+fn serialize_message_payload<S: serde::Serializer>(
+    payload: &Option<Map<String, Value>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match payload {
+        Some(payload) => payload.serialize(serializer),
+        None => serializer.serialize_bool(false),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListOrganizationsQuery<'a> {
+    /// Fuzzy search over the id, name and description
+    pub search: Option<&'a str>,
+    pub pagination: Option<Pagination>,
+}
+
+impl<'a> ListOrganizationsQuery<'a> {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+
+        if let Some(search) = self.search {
+            query.push(("q", search.to_string()));
+        }
+
+        if let Some(pagination) = self.pagination {
+            query.extend(pagination.to_query());
+        }
+
+        query
+    }
+}
+
+impl ManagementClient {
+    pub async fn list_organizations(
+        &self,
+        query: ListOrganizationsQuery<'_>,
+    ) -> Result<Page<Organization>, LogtoError> {
+        let request = self.request(Method::GET, &["organizations"]).await?;
+
+        self.send_page(request.query(&query.to_query())).await
+    }
+
+    pub async fn get_organization(
+        &self,
+        organization_id: &str,
+    ) -> Result<Organization, LogtoError> {
+        let request = self
+            .request(Method::GET, &["organizations", organization_id])
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_organization(
+        &self,
+        organization: CreateOrganization<'_>,
+    ) -> Result<Organization, LogtoError> {
+        let request = self.request(Method::POST, &["organizations"]).await?;
+
+        self.send(request.json(&organization)).await
+    }
+
+    pub async fn update_organization(
+        &self,
+        organization_id: &str,
+        organization: UpdateOrganization<'_>,
+    ) -> Result<Organization, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["organizations", organization_id])
+            .await?;
+
+        self.send(request.json(&organization)).await
+    }
+
+    pub async fn delete_organization(&self, organization_id: &str) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["organizations", organization_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_members(
+        &self,
+        organization_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<OrganizationMember>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["organizations", organization_id, "users"])
+            .await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    pub async fn add_organization_members(
+        &self,
+        organization_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::POST, &["organizations", organization_id, "users"])
+            .await?;
+
+        self.send_empty(request.json(&json!({ "userIds": user_ids })))
+            .await
+    }
+
+    pub async fn remove_organization_member(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["organizations", organization_id, "users", user_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_member_roles(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<OrganizationRole>, LogtoError> {
+        let request = self
+            .request(
+                Method::GET,
+                &["organizations", organization_id, "users", user_id, "roles"],
+            )
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn assign_organization_member_roles(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+        organization_role_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::POST,
+                &["organizations", organization_id, "users", user_id, "roles"],
+            )
+            .await?;
+
+        self.send_empty(request.json(&json!({ "organizationRoleIds": organization_role_ids })))
+            .await
+    }
+
+    pub async fn remove_organization_member_role(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+        organization_role_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &[
+                    "organizations",
+                    organization_id,
+                    "users",
+                    user_id,
+                    "roles",
+                    organization_role_id,
+                ],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_roles(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<OrganizationRole>, LogtoError> {
+        let request = self.request(Method::GET, &["organization-roles"]).await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    pub async fn create_organization_role(
+        &self,
+        role: CreateOrganizationRole<'_>,
+    ) -> Result<OrganizationRole, LogtoError> {
+        let request = self.request(Method::POST, &["organization-roles"]).await?;
+
+        self.send(request.json(&role)).await
+    }
+
+    pub async fn update_organization_role(
+        &self,
+        organization_role_id: &str,
+        role: OrganizationTemplateEntry<'_>,
+    ) -> Result<OrganizationRole, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["organization-roles", organization_role_id])
+            .await?;
+
+        self.send(request.json(&role)).await
+    }
+
+    pub async fn delete_organization_role(
+        &self,
+        organization_role_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["organization-roles", organization_role_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_role_scopes(
+        &self,
+        organization_role_id: &str,
+    ) -> Result<Vec<OrganizationScope>, LogtoError> {
+        let request = self
+            .request(
+                Method::GET,
+                &["organization-roles", organization_role_id, "scopes"],
+            )
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn assign_organization_role_scopes(
+        &self,
+        organization_role_id: &str,
+        organization_scope_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::POST,
+                &["organization-roles", organization_role_id, "scopes"],
+            )
+            .await?;
+
+        self.send_empty(request.json(&json!({ "organizationScopeIds": organization_scope_ids })))
+            .await
+    }
+
+    pub async fn remove_organization_role_scope(
+        &self,
+        organization_role_id: &str,
+        organization_scope_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &[
+                    "organization-roles",
+                    organization_role_id,
+                    "scopes",
+                    organization_scope_id,
+                ],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_scopes(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<OrganizationScope>, LogtoError> {
+        let request = self.request(Method::GET, &["organization-scopes"]).await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    pub async fn create_organization_scope(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<OrganizationScope, LogtoError> {
+        let request = self.request(Method::POST, &["organization-scopes"]).await?;
+
+        self.send(request.json(&OrganizationTemplateEntry {
+            name: Some(name),
+            description,
+        }))
+        .await
+    }
+
+    pub async fn update_organization_scope(
+        &self,
+        organization_scope_id: &str,
+        scope: OrganizationTemplateEntry<'_>,
+    ) -> Result<OrganizationScope, LogtoError> {
+        let request = self
+            .request(
+                Method::PATCH,
+                &["organization-scopes", organization_scope_id],
+            )
+            .await?;
+
+        self.send(request.json(&scope)).await
+    }
+
+    pub async fn delete_organization_scope(
+        &self,
+        organization_scope_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["organization-scopes", organization_scope_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_organization_invitations(
+        &self,
+        organization_id: Option<&str>,
+    ) -> Result<Vec<OrganizationInvitation>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["organization-invitations"])
+            .await?;
+
+        let query: Vec<_> = organization_id
+            .map(|id| ("organizationId", id))
+            .into_iter()
+            .collect();
+
+        self.send(request.query(&query)).await
+    }
+
+    pub async fn get_organization_invitation(
+        &self,
+        invitation_id: &str,
+    ) -> Result<OrganizationInvitation, LogtoError> {
+        let request = self
+            .request(Method::GET, &["organization-invitations", invitation_id])
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_organization_invitation(
+        &self,
+        invitation: CreateOrganizationInvitation<'_>,
+    ) -> Result<OrganizationInvitation, LogtoError> {
+        let request = self
+            .request(Method::POST, &["organization-invitations"])
+            .await?;
+
+        self.send(request.json(&invitation)).await
+    }
+
+    /// Sends the invitation email again with the given template variables
+    pub async fn resend_organization_invitation(
+        &self,
+        invitation_id: &str,
+        message_payload: Map<String, Value>,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::POST,
+                &["organization-invitations", invitation_id, "message"],
+            )
+            .await?;
+
+        self.send_empty(request.json(&message_payload)).await
+    }
+
+    /// Marks a pending invitation as accepted by `user_id` and adds the user
+    /// to the organization with the invitation's roles
+    pub async fn accept_organization_invitation(
+        &self,
+        invitation_id: &str,
+        user_id: &str,
+    ) -> Result<OrganizationInvitation, LogtoError> {
+        let request = self
+            .request(
+                Method::PUT,
+                &["organization-invitations", invitation_id, "status"],
+            )
+            .await?;
+
+        self.send(request.json(&json!({
+            "status": InvitationStatus::Accepted,
+            "acceptedUserId": user_id,
+        })))
+        .await
+    }
+
+    pub async fn revoke_organization_invitation(
+        &self,
+        invitation_id: &str,
+    ) -> Result<OrganizationInvitation, LogtoError> {
+        let request = self
+            .request(
+                Method::PUT,
+                &["organization-invitations", invitation_id, "status"],
+            )
+            .await?;
+
+        self.send(request.json(&json!({ "status": InvitationStatus::Revoked })))
+            .await
+    }
+
+    pub async fn delete_organization_invitation(
+        &self,
+        invitation_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["organization-invitations", invitation_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const INVITATION: &str = r#"{
+        "id": "invitation_id",
+        "tenantId": "default",
+        "inviterId": null,
+        "invitee": "jane@example.com",
+        "acceptedUserId": null,
+        "organizationId": "organization_id",
+        "status": "Pending",
+        "organizationRoles": [{ "id": "role_id", "name": "admin" }],
+        "createdAt": 1700000000000,
+        "updatedAt": 1700000000000,
+        "expiresAt": 1700604800000
+    }"#;
+
+    #[tokio::test]
+    async fn test_create_organization() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let mut custom_data = Map::new();
+        custom_data.insert("plan".to_string(), "enterprise".into());
+
+        server
+            .mock("POST", "/api/organizations")
+            .match_header("authorization", "Bearer management_token")
+            .match_body(Matcher::Json(json!({
+                "name": "Acme",
+                "customData": { "plan": "enterprise" },
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{
+                    "id": "organization_id",
+                    "tenantId": "default",
+                    "name": "Acme",
+                    "description": null,
+                    "customData": { "plan": "enterprise" },
+                    "isMfaRequired": false,
+                    "branding": {},
+                    "createdAt": 1700000000000
+                }"#,
+            )
+            .create();
+
+        let organization = client
+            .create_organization(CreateOrganization {
+                name: "Acme",
+                custom_data: Some(custom_data),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(organization.id, "organization_id");
+        assert_eq!(organization.custom_data["plan"], "enterprise");
+    }
+
+    #[tokio::test]
+    async fn test_list_organization_members() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/organizations/organization_id/users")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("total-number", "1")
+            .with_body(
+                r#"[{
+                    "id": "user_id",
+                    "username": "jane",
+                    "primaryEmail": null,
+                    "primaryPhone": null,
+                    "name": null,
+                    "avatar": null,
+                    "applicationId": null,
+                    "lastSignInAt": null,
+                    "createdAt": 1700000000000,
+                    "updatedAt": 1700000000000,
+                    "organizationRoles": [{ "id": "role_id", "name": "admin" }]
+                }]"#,
+            )
+            .create();
+
+        let page = client
+            .list_organization_members("organization_id", None)
+            .await
+            .unwrap();
+
+        assert_eq!(page.items[0].user.id, "user_id");
+        assert_eq!(page.items[0].organization_roles[0].name, "admin");
+    }
+
+    #[tokio::test]
+    async fn test_create_organization_invitation() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/organization-invitations")
+            .match_body(Matcher::Json(json!({
+                "invitee": "jane@example.com",
+                "organizationId": "organization_id",
+                "expiresAt": 1700604800000u64,
+                "organizationRoleIds": ["role_id"],
+                "messagePayload": false,
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(INVITATION)
+            .create();
+
+        let invitation = client
+            .create_organization_invitation(CreateOrganizationInvitation {
+                invitee: "jane@example.com",
+                organization_id: "organization_id",
+                expires_at: 1700604800000,
+                organization_role_ids: vec!["role_id"],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(invitation.status, InvitationStatus::Pending);
+        assert_eq!(invitation.organization_roles[0].id, "role_id");
+    }
+
+    #[tokio::test]
+    async fn test_accept_organization_invitation() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("PUT", "/api/organization-invitations/invitation_id/status")
+            .match_body(Matcher::Json(json!({
+                "status": "Accepted",
+                "acceptedUserId": "user_id",
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(INVITATION.replace("\"Pending\"", "\"Accepted\"").replace(
+                "\"acceptedUserId\": null",
+                "\"acceptedUserId\": \"user_id\"",
+            ))
+            .create();
+
+        let invitation = client
+            .accept_organization_invitation("invitation_id", "user_id")
+            .await
+            .unwrap();
+
+        assert_eq!(invitation.status, InvitationStatus::Accepted);
+        assert_eq!(invitation.accepted_user_id.as_deref(), Some("user_id"));
+    }
+}
@@ -0,0 +1,1413 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::error::LogtoError,
+    management::{
+        client::{ManagementClient, Page, Pagination},
+        organizations::{CreateOrganizationRole, OrganizationTemplateEntry},
+        resources::{CreateResource, ResourceScopeEntry, UpdateResource},
+        roles::{CreateRole, ListRolesQuery, RoleType, UpdateRole},
+    },
+};
+
+const PAGE_SIZE: u32 = 100;
+
+/// The desired state of a tenant's API resources, roles and organization
+/// template. Entities missing from the manifest are left alone unless
+/// `ReconcileOptions::prune` is set, but the scopes of a listed role, and of a
+/// listed resource that sets `scopes`, are always made to match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub resources: Vec<ResourceManifest>,
+    #[serde(default)]
+    pub roles: Vec<RoleManifest>,
+    #[serde(default)]
+    pub organization_scopes: Vec<ScopeManifest>,
+    #[serde(default)]
+    pub organization_roles: Vec<OrganizationRoleManifest>,
+}
+
+/// Resources are matched by indicator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceManifest {
+    pub name: String,
+    pub indicator: String,
+    /// In seconds, unmanaged if unset
+    pub access_token_ttl: Option<u64>,
+    /// Unmanaged if unset, while an empty list deletes every scope
+    pub scopes: Option<Vec<ScopeManifest>>,
+}
+
+/// Scopes and roles are matched by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopeManifest {
+    pub name: String,
+    /// Unmanaged if unset
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleManifest {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type", default)]
+    pub role_type: RoleType,
+    #[serde(default)]
+    pub scopes: Vec<RoleScopeRef>,
+}
+
+/// A scope of a resource listed in the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleScopeRef {
+    /// Resource indicator
+    pub resource: String,
+    pub scope: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrganizationRoleManifest {
+    pub name: String,
+    /// Unmanaged if unset
+    pub description: Option<String>,
+    /// Names of organization scopes listed in the manifest
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ReconcileError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Yaml(serde_yaml::Error),
+    UnsupportedFormat(String),
+    Duplicate(String),
+    UnknownReference(String),
+    /// The type of an existing role can't be changed
+    RoleTypeChanged(String),
+    Api(LogtoError),
+}
+
+impl fmt::Display for ReconcileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "couldn't read manifest: {}", e),
+            Self::Toml(e) => write!(f, "invalid TOML manifest: {}", e),
+            Self::Yaml(e) => write!(f, "invalid YAML manifest: {}", e),
+            Self::UnsupportedFormat(path) => {
+                write!(f, "manifest {} is neither TOML nor YAML", path)
+            }
+            Self::Duplicate(name) => write!(f, "{} is listed more than once", name),
+            Self::UnknownReference(name) => write!(f, "{} is not in the manifest", name),
+            Self::RoleTypeChanged(name) => write!(f, "role {} has another type", name),
+            Self::Api(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReconcileError {}
+
+impl From<std::io::Error> for ReconcileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ReconcileError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ReconcileError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Yaml(e)
+    }
+}
+
+impl From<LogtoError> for ReconcileError {
+    fn from(e: LogtoError) -> Self {
+        Self::Api(e)
+    }
+}
+
+impl Manifest {
+    pub fn from_toml(manifest: &str) -> Result<Self, ReconcileError> {
+        let manifest: Self = toml::from_str(manifest)?;
+        manifest.validate()?;
+
+        Ok(manifest)
+    }
+
+    pub fn from_yaml(manifest: &str) -> Result<Self, ReconcileError> {
+        let manifest: Self = serde_yaml::from_str(manifest)?;
+        manifest.validate()?;
+
+        Ok(manifest)
+    }
+
+    /// Loads a `.toml`, `.yaml` or `.yml` manifest
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReconcileError> {
+        let path = path.as_ref();
+        let manifest = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&manifest),
+            Some("yaml" | "yml") => Self::from_yaml(&manifest),
+            _ => Err(ReconcileError::UnsupportedFormat(
+                path.display().to_string(),
+            )),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ReconcileError> {
+        let mut scopes = HashSet::new();
+        // Their live scopes are only known when planning
+        let mut unmanaged_scopes = HashSet::new();
+        unique(self.resources.iter().map(|resource| &resource.indicator))?;
+
+        for resource in &self.resources {
+            let Some(resource_scopes) = &resource.scopes else {
+                unmanaged_scopes.insert(resource.indicator.as_str());
+                continue;
+            };
+
+            unique(resource_scopes.iter().map(|scope| &scope.name))?;
+            scopes.extend(
+                resource_scopes
+                    .iter()
+                    .map(|scope| (resource.indicator.as_str(), scope.name.as_str())),
+            );
+        }
+
+        unique(self.roles.iter().map(|role| &role.name))?;
+        for role in &self.roles {
+            for scope in &role.scopes {
+                if !unmanaged_scopes.contains(scope.resource.as_str())
+                    && !scopes.contains(&(scope.resource.as_str(), scope.scope.as_str()))
+                {
+                    return Err(ReconcileError::UnknownReference(format!(
+                        "scope {} of {}",
+                        scope.scope, scope.resource
+                    )));
+                }
+            }
+        }
+
+        unique(self.organization_scopes.iter().map(|scope| &scope.name))?;
+        unique(self.organization_roles.iter().map(|role| &role.name))?;
+        for role in &self.organization_roles {
+            for scope in &role.scopes {
+                if !self.organization_scopes.iter().any(|s| &s.name == scope) {
+                    return Err(ReconcileError::UnknownReference(format!(
+                        "organization scope {}",
+                        scope
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unique<'a>(names: impl Iterator<Item = &'a String>) -> Result<(), ReconcileError> {
+    let mut seen = HashSet::new();
+
+    for name in names {
+        if !seen.insert(name) {
+            return Err(ReconcileError::Duplicate(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReconcileOptions {
+    /// Also delete resources, roles and organization roles and scopes that
+    /// aren't in the manifest. The Management API resource is always kept.
+    pub prune: bool,
+    /// Let `prune` delete roles carrying Management API scopes too, which can
+    /// lock this client out of the tenant
+    pub prune_management_roles: bool,
+    /// Only compute the plan
+    pub dry_run: bool,
+}
+
+/// A single step of a `Plan`. Updates, removals and deletions target live
+/// entities by id, creations and assignments are resolved by name on apply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    CreateResource {
+        indicator: String,
+        name: String,
+        access_token_ttl: Option<u64>,
+    },
+    UpdateResource {
+        id: String,
+        indicator: String,
+        name: Option<String>,
+        access_token_ttl: Option<u64>,
+    },
+    DeleteResource {
+        id: String,
+        indicator: String,
+    },
+    CreateResourceScope {
+        indicator: String,
+        name: String,
+        description: Option<String>,
+    },
+    UpdateResourceScope {
+        resource_id: String,
+        id: String,
+        indicator: String,
+        name: String,
+        description: String,
+    },
+    DeleteResourceScope {
+        resource_id: String,
+        id: String,
+        indicator: String,
+        name: String,
+    },
+    CreateRole {
+        name: String,
+        description: String,
+        role_type: RoleType,
+    },
+    UpdateRole {
+        id: String,
+        name: String,
+        description: String,
+    },
+    DeleteRole {
+        id: String,
+        name: String,
+    },
+    AssignRoleScopes {
+        role: String,
+        scopes: Vec<RoleScopeRef>,
+    },
+    RemoveRoleScope {
+        role_id: String,
+        role: String,
+        scope_id: String,
+        scope: RoleScopeRef,
+    },
+    CreateOrganizationScope {
+        name: String,
+        description: Option<String>,
+    },
+    UpdateOrganizationScope {
+        id: String,
+        name: String,
+        description: String,
+    },
+    DeleteOrganizationScope {
+        id: String,
+        name: String,
+    },
+    CreateOrganizationRole {
+        name: String,
+        description: Option<String>,
+    },
+    UpdateOrganizationRole {
+        id: String,
+        name: String,
+        description: String,
+    },
+    DeleteOrganizationRole {
+        id: String,
+        name: String,
+    },
+    AssignOrganizationRoleScopes {
+        role: String,
+        scopes: Vec<String>,
+    },
+    RemoveOrganizationRoleScope {
+        role_id: String,
+        role: String,
+        scope_id: String,
+        scope: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateResource {
+                indicator, name, ..
+            } => write!(f, "+ resource {} ({})", indicator, name),
+            Self::UpdateResource { indicator, .. } => write!(f, "~ resource {}", indicator),
+            Self::DeleteResource { indicator, .. } => write!(f, "- resource {}", indicator),
+            Self::CreateResourceScope {
+                indicator, name, ..
+            } => write!(f, "+ scope {} of {}", name, indicator),
+            Self::UpdateResourceScope {
+                indicator, name, ..
+            } => write!(f, "~ scope {} of {}", name, indicator),
+            Self::DeleteResourceScope {
+                indicator, name, ..
+            } => write!(f, "- scope {} of {}", name, indicator),
+            Self::CreateRole {
+                name, role_type, ..
+            } => write!(f, "+ role {} ({})", name, role_type.as_str()),
+            Self::UpdateRole { name, .. } => write!(f, "~ role {}", name),
+            Self::DeleteRole { name, .. } => write!(f, "- role {}", name),
+            Self::AssignRoleScopes { role, scopes } => {
+                for (i, scope) in scopes.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(
+                        f,
+                        "+ role {} scope {} of {}",
+                        role, scope.scope, scope.resource
+                    )?;
+                }
+                Ok(())
+            }
+            Self::RemoveRoleScope { role, scope, .. } => write!(
+                f,
+                "- role {} scope {} of {}",
+                role, scope.scope, scope.resource
+            ),
+            Self::CreateOrganizationScope { name, .. } => {
+                write!(f, "+ organization scope {}", name)
+            }
+            Self::UpdateOrganizationScope { name, .. } => {
+                write!(f, "~ organization scope {}", name)
+            }
+            Self::DeleteOrganizationScope { name, .. } => {
+                write!(f, "- organization scope {}", name)
+            }
+            Self::CreateOrganizationRole { name, .. } => {
+                write!(f, "+ organization role {}", name)
+            }
+            Self::UpdateOrganizationRole { name, .. } => {
+                write!(f, "~ organization role {}", name)
+            }
+            Self::DeleteOrganizationRole { name, .. } => {
+                write!(f, "- organization role {}", name)
+            }
+            Self::AssignOrganizationRoleScopes { role, scopes } => {
+                for (i, scope) in scopes.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "+ organization role {} scope {}", role, scope)?;
+                }
+                Ok(())
+            }
+            Self::RemoveOrganizationRoleScope { role, scope, .. } => {
+                write!(f, "- organization role {} scope {}", role, scope)
+            }
+        }
+    }
+}
+
+/// Live ids by name, completed with the ids of created entities on apply
+#[derive(Debug, Clone, Default)]
+struct Ids {
+    /// By indicator
+    resources: HashMap<String, String>,
+    /// By indicator and scope name
+    scopes: HashMap<(String, String), String>,
+    roles: HashMap<String, String>,
+    organization_scopes: HashMap<String, String>,
+    organization_roles: HashMap<String, String>,
+}
+
+impl Ids {
+    fn get<'a, K>(
+        ids: &'a HashMap<K, String>,
+        key: &K,
+        name: &str,
+    ) -> Result<&'a str, ReconcileError>
+    where
+        K: std::hash::Hash + Eq,
+    {
+        ids.get(key)
+            .map(String::as_str)
+            .ok_or_else(|| ReconcileError::UnknownReference(name.to_string()))
+    }
+}
+
+/// Changes that bring a tenant to a manifest, in the order they're applied.
+/// Its `Display` output is the human-readable plan, e.g. for a dry run.
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    pub changes: Vec<Change>,
+    ids: Ids,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No changes");
+        }
+
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fetches every page of a list endpoint
+async fn all_pages<T, F, Fut>(fetch: F) -> Result<Vec<T>, LogtoError>
+where
+    F: Fn(Pagination) -> Fut,
+    Fut: Future<Output = Result<Page<T>, LogtoError>>,
+{
+    let mut items = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let batch = fetch(Pagination {
+            page,
+            page_size: PAGE_SIZE,
+        })
+        .await?
+        .items;
+        let is_last = batch.len() < PAGE_SIZE as usize;
+
+        items.extend(batch);
+
+        if is_last {
+            return Ok(items);
+        }
+
+        page += 1;
+    }
+}
+
+impl ManagementClient {
+    /// Plans, then applies unless `dry_run` is set. Returns the plan so it
+    /// can be printed either way.
+    pub async fn reconcile(
+        &self,
+        manifest: &Manifest,
+        options: ReconcileOptions,
+    ) -> Result<Plan, ReconcileError> {
+        let plan = self.plan(manifest, options).await?;
+
+        if !options.dry_run {
+            self.apply(&plan).await?;
+        }
+
+        Ok(plan)
+    }
+
+    /// Computes the changes from the live tenant to `manifest`
+    pub async fn plan(
+        &self,
+        manifest: &Manifest,
+        options: ReconcileOptions,
+    ) -> Result<Plan, ReconcileError> {
+        let mut ids = Ids::default();
+        let mut changes = Vec::new();
+        let mut removals = Vec::new();
+        let mut deletions = Vec::new();
+
+        let live_resources = self.list_resources(true).await?;
+        let mut indicators = HashMap::new();
+
+        for resource in &live_resources {
+            ids.resources
+                .insert(resource.indicator.clone(), resource.id.clone());
+            indicators.insert(resource.id.as_str(), resource.indicator.as_str());
+
+            for scope in resource.scopes.iter().flatten() {
+                ids.scopes.insert(
+                    (resource.indicator.clone(), scope.name.clone()),
+                    scope.id.clone(),
+                );
+            }
+        }
+
+        for desired in &manifest.resources {
+            let Some(live) = live_resources
+                .iter()
+                .find(|resource| resource.indicator == desired.indicator)
+            else {
+                changes.push(Change::CreateResource {
+                    indicator: desired.indicator.clone(),
+                    name: desired.name.clone(),
+                    access_token_ttl: desired.access_token_ttl,
+                });
+                changes.extend(desired.scopes.iter().flatten().map(|scope| {
+                    Change::CreateResourceScope {
+                        indicator: desired.indicator.clone(),
+                        name: scope.name.clone(),
+                        description: scope.description.clone(),
+                    }
+                }));
+                continue;
+            };
+
+            let name = Some(&desired.name).filter(|name| **name != live.name);
+            let access_token_ttl = desired
+                .access_token_ttl
+                .filter(|ttl| *ttl != live.access_token_ttl);
+
+            if name.is_some() || access_token_ttl.is_some() {
+                changes.push(Change::UpdateResource {
+                    id: live.id.clone(),
+                    indicator: live.indicator.clone(),
+                    name: name.cloned(),
+                    access_token_ttl,
+                });
+            }
+
+            let Some(desired_scopes) = &desired.scopes else {
+                continue;
+            };
+            let live_scopes = live.scopes.as_deref().unwrap_or_default();
+
+            for scope in desired_scopes {
+                match live_scopes.iter().find(|live| live.name == scope.name) {
+                    None => changes.push(Change::CreateResourceScope {
+                        indicator: live.indicator.clone(),
+                        name: scope.name.clone(),
+                        description: scope.description.clone(),
+                    }),
+                    Some(live_scope) => {
+                        if let Some(description) = scope
+                            .description
+                            .as_ref()
+                            .filter(|d| Some(*d) != live_scope.description.as_ref())
+                        {
+                            changes.push(Change::UpdateResourceScope {
+                                resource_id: live.id.clone(),
+                                id: live_scope.id.clone(),
+                                indicator: live.indicator.clone(),
+                                name: scope.name.clone(),
+                                description: description.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            for live_scope in live_scopes {
+                if !desired_scopes.iter().any(|s| s.name == live_scope.name) {
+                    deletions.push(Change::DeleteResourceScope {
+                        resource_id: live.id.clone(),
+                        id: live_scope.id.clone(),
+                        indicator: live.indicator.clone(),
+                        name: live_scope.name.clone(),
+                    });
+                }
+            }
+        }
+
+        if options.prune {
+            for live in &live_resources {
+                if live.indicator != self.resource
+                    && !manifest
+                        .resources
+                        .iter()
+                        .any(|resource| resource.indicator == live.indicator)
+                {
+                    deletions.push(Change::DeleteResource {
+                        id: live.id.clone(),
+                        indicator: live.indicator.clone(),
+                    });
+                }
+            }
+        }
+
+        let live_roles = all_pages(|pagination| {
+            self.list_roles(ListRolesQuery {
+                pagination: Some(pagination),
+                ..Default::default()
+            })
+        })
+        .await?;
+
+        for role in &live_roles {
+            ids.roles.insert(role.name.clone(), role.id.clone());
+        }
+
+        for desired in &manifest.roles {
+            let Some(live) = live_roles.iter().find(|role| role.name == desired.name) else {
+                changes.push(Change::CreateRole {
+                    name: desired.name.clone(),
+                    description: desired.description.clone(),
+                    role_type: desired.role_type,
+                });
+                if !desired.scopes.is_empty() {
+                    changes.push(Change::AssignRoleScopes {
+                        role: desired.name.clone(),
+                        scopes: desired.scopes.clone(),
+                    });
+                }
+                continue;
+            };
+
+            if live.role_type != desired.role_type {
+                return Err(ReconcileError::RoleTypeChanged(desired.name.clone()));
+            }
+
+            if live.description != desired.description {
+                changes.push(Change::UpdateRole {
+                    id: live.id.clone(),
+                    name: live.name.clone(),
+                    description: desired.description.clone(),
+                });
+            }
+
+            let live_scopes: Vec<(RoleScopeRef, String)> = self
+                .list_role_scopes(&live.id)
+                .await?
+                .into_iter()
+                .map(|scope| {
+                    let resource = indicators
+                        .get(scope.resource_id.as_str())
+                        .map_or(scope.resource_id.clone(), |indicator| indicator.to_string());
+
+                    (
+                        RoleScopeRef {
+                            resource,
+                            scope: scope.name,
+                        },
+                        scope.id,
+                    )
+                })
+                .collect();
+
+            let missing: Vec<RoleScopeRef> = desired
+                .scopes
+                .iter()
+                .filter(|scope| !live_scopes.iter().any(|(live, _)| live == *scope))
+                .cloned()
+                .collect();
+
+            if !missing.is_empty() {
+                changes.push(Change::AssignRoleScopes {
+                    role: desired.name.clone(),
+                    scopes: missing,
+                });
+            }
+
+            for (scope, scope_id) in live_scopes {
+                if !desired.scopes.contains(&scope) {
+                    removals.push(Change::RemoveRoleScope {
+                        role_id: live.id.clone(),
+                        role: live.name.clone(),
+                        scope_id,
+                        scope,
+                    });
+                }
+            }
+        }
+
+        if options.prune {
+            let management_api_id = ids.resources.get(&self.resource);
+
+            for live in &live_roles {
+                if manifest.roles.iter().any(|role| role.name == live.name) {
+                    continue;
+                }
+
+                if !options.prune_management_roles
+                    && self
+                        .list_role_scopes(&live.id)
+                        .await?
+                        .iter()
+                        .any(|scope| Some(&scope.resource_id) == management_api_id)
+                {
+                    continue;
+                }
+
+                deletions.push(Change::DeleteRole {
+                    id: live.id.clone(),
+                    name: live.name.clone(),
+                });
+            }
+        }
+
+        let live_organization_scopes =
+            all_pages(|pagination| self.list_organization_scopes(Some(pagination))).await?;
+
+        for scope in &live_organization_scopes {
+            ids.organization_scopes
+                .insert(scope.name.clone(), scope.id.clone());
+        }
+
+        for desired in &manifest.organization_scopes {
+            match live_organization_scopes
+                .iter()
+                .find(|scope| scope.name == desired.name)
+            {
+                None => changes.push(Change::CreateOrganizationScope {
+                    name: desired.name.clone(),
+                    description: desired.description.clone(),
+                }),
+                Some(live) => {
+                    if let Some(description) = desired
+                        .description
+                        .as_ref()
+                        .filter(|d| Some(*d) != live.description.as_ref())
+                    {
+                        changes.push(Change::UpdateOrganizationScope {
+                            id: live.id.clone(),
+                            name: live.name.clone(),
+                            description: description.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if options.prune {
+            for live in &live_organization_scopes {
+                if !manifest
+                    .organization_scopes
+                    .iter()
+                    .any(|scope| scope.name == live.name)
+                {
+                    deletions.push(Change::DeleteOrganizationScope {
+                        id: live.id.clone(),
+                        name: live.name.clone(),
+                    });
+                }
+            }
+        }
+
+        let live_organization_roles =
+            all_pages(|pagination| self.list_organization_roles(Some(pagination))).await?;
+
+        for role in &live_organization_roles {
+            ids.organization_roles
+                .insert(role.name.clone(), role.id.clone());
+        }
+
+        for desired in &manifest.organization_roles {
+            let Some(live) = live_organization_roles
+                .iter()
+                .find(|role| role.name == desired.name)
+            else {
+                changes.push(Change::CreateOrganizationRole {
+                    name: desired.name.clone(),
+                    description: desired.description.clone(),
+                });
+                if !desired.scopes.is_empty() {
+                    changes.push(Change::AssignOrganizationRoleScopes {
+                        role: desired.name.clone(),
+                        scopes: desired.scopes.clone(),
+                    });
+                }
+                continue;
+            };
+
+            if let Some(description) = desired
+                .description
+                .as_ref()
+                .filter(|d| Some(*d) != live.description.as_ref())
+            {
+                changes.push(Change::UpdateOrganizationRole {
+                    id: live.id.clone(),
+                    name: live.name.clone(),
+                    description: description.clone(),
+                });
+            }
+
+            let live_scopes = self.list_organization_role_scopes(&live.id).await?;
+
+            let missing: Vec<String> = desired
+                .scopes
+                .iter()
+                .filter(|scope| !live_scopes.iter().any(|live| live.name == **scope))
+                .cloned()
+                .collect();
+
+            if !missing.is_empty() {
+                changes.push(Change::AssignOrganizationRoleScopes {
+                    role: desired.name.clone(),
+                    scopes: missing,
+                });
+            }
+
+            for scope in live_scopes {
+                if !desired.scopes.contains(&scope.name) {
+                    removals.push(Change::RemoveOrganizationRoleScope {
+                        role_id: live.id.clone(),
+                        role: live.name.clone(),
+                        scope_id: scope.id,
+                        scope: scope.name,
+                    });
+                }
+            }
+        }
+
+        if options.prune {
+            for live in &live_organization_roles {
+                if !manifest
+                    .organization_roles
+                    .iter()
+                    .any(|role| role.name == live.name)
+                {
+                    deletions.push(Change::DeleteOrganizationRole {
+                        id: live.id.clone(),
+                        name: live.name.clone(),
+                    });
+                }
+            }
+        }
+
+        // Unassign before deleting, a deleted scope can't be unassigned
+        changes.extend(removals);
+        changes.extend(deletions);
+
+        Ok(Plan { changes, ids })
+    }
+
+    /// Applies `plan` step by step. If a step fails, planning again picks up
+    /// from where it stopped.
+    pub async fn apply(&self, plan: &Plan) -> Result<(), ReconcileError> {
+        let mut ids = plan.ids.clone();
+
+        for change in &plan.changes {
+            match change {
+                Change::CreateResource {
+                    indicator,
+                    name,
+                    access_token_ttl,
+                } => {
+                    let resource = self
+                        .create_resource(CreateResource {
+                            name,
+                            indicator,
+                            access_token_ttl: *access_token_ttl,
+                        })
+                        .await?;
+                    ids.resources.insert(resource.indicator, resource.id);
+                }
+                Change::UpdateResource {
+                    id,
+                    name,
+                    access_token_ttl,
+                    ..
+                } => {
+                    self.update_resource(
+                        id,
+                        UpdateResource {
+                            name: name.as_deref(),
+                            access_token_ttl: *access_token_ttl,
+                        },
+                    )
+                    .await?;
+                }
+                Change::DeleteResource { id, .. } => self.delete_resource(id).await?,
+                Change::CreateResourceScope {
+                    indicator,
+                    name,
+                    description,
+                } => {
+                    let resource_id = Ids::get(&ids.resources, indicator, indicator)?;
+                    let scope = self
+                        .create_resource_scope(resource_id, name, description.as_deref())
+                        .await?;
+                    ids.scopes.insert((indicator.clone(), scope.name), scope.id);
+                }
+                Change::UpdateResourceScope {
+                    resource_id,
+                    id,
+                    description,
+                    ..
+                } => {
+                    self.update_resource_scope(
+                        resource_id,
+                        id,
+                        ResourceScopeEntry {
+                            name: None,
+                            description: Some(description),
+                        },
+                    )
+                    .await?;
+                }
+                Change::DeleteResourceScope {
+                    resource_id, id, ..
+                } => self.delete_resource_scope(resource_id, id).await?,
+                Change::CreateRole {
+                    name,
+                    description,
+                    role_type,
+                } => {
+                    let role = self
+                        .create_role(CreateRole {
+                            name,
+                            description,
+                            role_type: *role_type,
+                            ..Default::default()
+                        })
+                        .await?;
+                    ids.roles.insert(role.name, role.id);
+                }
+                Change::UpdateRole {
+                    id, description, ..
+                } => {
+                    self.update_role(
+                        id,
+                        UpdateRole {
+                            description: Some(description),
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                }
+                Change::DeleteRole { id, .. } => self.delete_role(id).await?,
+                Change::AssignRoleScopes { role, scopes } => {
+                    let role_id = Ids::get(&ids.roles, role, role)?;
+                    let scope_ids = scopes
+                        .iter()
+                        .map(|scope| {
+                            Ids::get(
+                                &ids.scopes,
+                                &(scope.resource.clone(), scope.scope.clone()),
+                                &scope.scope,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.assign_role_scopes(role_id, &scope_ids).await?;
+                }
+                Change::RemoveRoleScope {
+                    role_id, scope_id, ..
+                } => self.remove_role_scope(role_id, scope_id).await?,
+                Change::CreateOrganizationScope { name, description } => {
+                    let scope = self
+                        .create_organization_scope(name, description.as_deref())
+                        .await?;
+                    ids.organization_scopes.insert(scope.name, scope.id);
+                }
+                Change::UpdateOrganizationScope {
+                    id, description, ..
+                } => {
+                    self.update_organization_scope(
+                        id,
+                        OrganizationTemplateEntry {
+                            name: None,
+                            description: Some(description),
+                        },
+                    )
+                    .await?;
+                }
+                Change::DeleteOrganizationScope { id, .. } => {
+                    self.delete_organization_scope(id).await?
+                }
+                Change::CreateOrganizationRole { name, description } => {
+                    let role = self
+                        .create_organization_role(CreateOrganizationRole {
+                            name,
+                            description: description.as_deref(),
+                            ..Default::default()
+                        })
+                        .await?;
+                    ids.organization_roles.insert(role.name, role.id);
+                }
+                Change::UpdateOrganizationRole {
+                    id, description, ..
+                } => {
+                    self.update_organization_role(
+                        id,
+                        OrganizationTemplateEntry {
+                            name: None,
+                            description: Some(description),
+                        },
+                    )
+                    .await?;
+                }
+                Change::DeleteOrganizationRole { id, .. } => {
+                    self.delete_organization_role(id).await?
+                }
+                Change::AssignOrganizationRoleScopes { role, scopes } => {
+                    let role_id = Ids::get(&ids.organization_roles, role, role)?;
+                    let scope_ids = scopes
+                        .iter()
+                        .map(|scope| Ids::get(&ids.organization_scopes, scope, scope))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    self.assign_organization_role_scopes(role_id, &scope_ids)
+                        .await?;
+                }
+                Change::RemoveOrganizationRoleScope {
+                    role_id, scope_id, ..
+                } => {
+                    self.remove_organization_role_scope(role_id, scope_id)
+                        .await?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+    use serde_json::json;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const MANIFEST: &str = r#"
+        [[resources]]
+        name = "Orders"
+        indicator = "https://orders.example.com"
+        access_token_ttl = 3600
+        scopes = [
+            { name = "read:orders", description = "Read orders" },
+            { name = "write:orders" },
+        ]
+
+        [[roles]]
+        name = "clerk"
+        description = "Handles orders"
+        scopes = [
+            { resource = "https://orders.example.com", scope = "read:orders" },
+            { resource = "https://orders.example.com", scope = "write:orders" },
+        ]
+
+        [[organization_scopes]]
+        name = "invite:member"
+
+        [[organization_roles]]
+        name = "admin"
+        scopes = ["invite:member"]
+    "#;
+
+    fn mock_json(server: &mut mockito::Server, path: &str, body: serde_json::Value) {
+        server
+            .mock("GET", path)
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .create();
+    }
+
+    #[test]
+    fn parse_manifest() {
+        let yaml = r#"
+            resources:
+              - name: Orders
+                indicator: https://orders.example.com
+                access_token_ttl: 3600
+                scopes:
+                  - name: read:orders
+                    description: Read orders
+                  - name: write:orders
+            roles:
+              - name: clerk
+                description: Handles orders
+                scopes:
+                  - resource: https://orders.example.com
+                    scope: read:orders
+                  - resource: https://orders.example.com
+                    scope: write:orders
+            organization_scopes:
+              - name: invite:member
+            organization_roles:
+              - name: admin
+                scopes: [invite:member]
+        "#;
+
+        let manifest = Manifest::from_toml(MANIFEST).unwrap();
+        assert_eq!(manifest, Manifest::from_yaml(yaml).unwrap());
+        assert_eq!(manifest.roles[0].role_type, RoleType::User);
+
+        let unknown_scope = MANIFEST.replace(r#"scope = "write:orders""#, r#"scope = "x""#);
+        assert!(matches!(
+            Manifest::from_toml(&unknown_scope),
+            Err(ReconcileError::UnknownReference(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn plan_against_live_tenant() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        mock_json(
+            &mut server,
+            "/api/resources",
+            json!([{
+                "id": "resource_id",
+                "name": "Orders",
+                "indicator": "https://orders.example.com",
+                "isDefault": false,
+                "accessTokenTtl": 3600,
+                "scopes": [
+                    { "id": "read_id", "resourceId": "resource_id", "name": "read:orders", "description": "Read orders", "createdAt": 0 },
+                    { "id": "old_id", "resourceId": "resource_id", "name": "delete:orders", "description": null, "createdAt": 0 },
+                ],
+            }]),
+        );
+        mock_json(
+            &mut server,
+            "/api/roles",
+            json!([{ "id": "role_id", "name": "clerk", "description": "Handles orders", "type": "User" }]),
+        );
+        mock_json(
+            &mut server,
+            "/api/roles/role_id/scopes",
+            json!([
+                { "id": "read_id", "resourceId": "resource_id", "name": "read:orders", "description": null, "createdAt": 0 },
+                { "id": "old_id", "resourceId": "resource_id", "name": "delete:orders", "description": null, "createdAt": 0 },
+            ]),
+        );
+        mock_json(
+            &mut server,
+            "/api/organization-scopes",
+            json!([{ "id": "invite_id", "name": "invite:member", "description": null }]),
+        );
+        mock_json(&mut server, "/api/organization-roles", json!([]));
+
+        let manifest = Manifest::from_toml(MANIFEST).unwrap();
+        let plan = client
+            .plan(&manifest, ReconcileOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            plan.to_string(),
+            "+ scope write:orders of https://orders.example.com\n\
+             + role clerk scope write:orders of https://orders.example.com\n\
+             + organization role admin\n\
+             + organization role admin scope invite:member\n\
+             - role clerk scope delete:orders of https://orders.example.com\n\
+             - scope delete:orders of https://orders.example.com\n"
+        );
+
+        server
+            .mock("POST", "/api/resources/resource_id/scopes")
+            .match_body(Matcher::Json(json!({ "name": "write:orders" })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({ "id": "write_id", "resourceId": "resource_id", "name": "write:orders", "description": null, "createdAt": 0 })
+                    .to_string(),
+            )
+            .create();
+        let assign_role_scopes = server
+            .mock("POST", "/api/roles/role_id/scopes")
+            .match_body(Matcher::Json(json!({ "scopeIds": ["write_id"] })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .expect(1)
+            .create();
+        server
+            .mock("POST", "/api/organization-roles")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({ "id": "admin_id", "name": "admin", "description": null }).to_string(),
+            )
+            .create();
+        let assign_organization_role_scopes = server
+            .mock("POST", "/api/organization-roles/admin_id/scopes")
+            .match_body(Matcher::Json(
+                json!({ "organizationScopeIds": ["invite_id"] }),
+            ))
+            .with_status(201)
+            .expect(1)
+            .create();
+        let remove_role_scope = server
+            .mock("DELETE", "/api/roles/role_id/scopes/old_id")
+            .with_status(204)
+            .expect(1)
+            .create();
+        let delete_scope = server
+            .mock("DELETE", "/api/resources/resource_id/scopes/old_id")
+            .with_status(204)
+            .expect(1)
+            .create();
+
+        client.apply(&plan).await.unwrap();
+
+        assign_role_scopes.assert();
+        assign_organization_role_scopes.assert();
+        remove_role_scope.assert();
+        delete_scope.assert();
+    }
+
+    #[tokio::test]
+    async fn resource_without_scopes_keeps_live_scopes() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        mock_json(
+            &mut server,
+            "/api/resources",
+            json!([{
+                "id": "resource_id",
+                "name": "Orders",
+                "indicator": "https://orders.example.com",
+                "isDefault": false,
+                "accessTokenTtl": 3600,
+                "scopes": [
+                    { "id": "read_id", "resourceId": "resource_id", "name": "read:orders", "description": null, "createdAt": 0 },
+                ],
+            }]),
+        );
+        mock_json(&mut server, "/api/roles", json!([]));
+        mock_json(&mut server, "/api/organization-scopes", json!([]));
+        mock_json(&mut server, "/api/organization-roles", json!([]));
+
+        // The role can reference a live scope the manifest doesn't list
+        let manifest = Manifest::from_toml(
+            r#"
+            [[resources]]
+            name = "Orders"
+            indicator = "https://orders.example.com"
+
+            [[roles]]
+            name = "reader"
+            description = "Reads orders"
+            scopes = [{ resource = "https://orders.example.com", scope = "read:orders" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.resources[0].scopes, None);
+
+        let plan = client
+            .plan(&manifest, ReconcileOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            plan.to_string(),
+            "+ role reader (User)\n\
+             + role reader scope read:orders of https://orders.example.com\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_doesnt_apply() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        mock_json(&mut server, "/api/resources", json!([]));
+        mock_json(&mut server, "/api/roles", json!([]));
+        mock_json(&mut server, "/api/organization-scopes", json!([]));
+        mock_json(&mut server, "/api/organization-roles", json!([]));
+        let create = server.mock("POST", Matcher::Any).expect(0).create();
+
+        let plan = client
+            .reconcile(
+                &Manifest::from_toml(MANIFEST).unwrap(),
+                ReconcileOptions {
+                    dry_run: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(plan.changes.len(), 8);
+        create.assert();
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_management_api() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        mock_json(
+            &mut server,
+            "/api/resources",
+            json!([
+                {
+                    "id": "management_api_id",
+                    "name": "Logto Management API",
+                    "indicator": "https://default.logto.app/api",
+                    "isDefault": false,
+                    "accessTokenTtl": 3600,
+                    "scopes": [
+                        { "id": "all_id", "resourceId": "management_api_id", "name": "all", "description": null, "createdAt": 0 },
+                    ],
+                },
+                {
+                    "id": "legacy_id",
+                    "name": "Legacy",
+                    "indicator": "https://legacy.example.com",
+                    "isDefault": false,
+                    "accessTokenTtl": 3600,
+                    "scopes": [],
+                },
+            ]),
+        );
+        mock_json(
+            &mut server,
+            "/api/roles",
+            json!([
+                { "id": "m2m_id", "name": "m2m admin", "description": "Management API access", "type": "MachineToMachine" },
+                { "id": "stale_id", "name": "stale", "description": "Unused", "type": "User" },
+            ]),
+        );
+        mock_json(
+            &mut server,
+            "/api/roles/m2m_id/scopes",
+            json!([
+                { "id": "all_id", "resourceId": "management_api_id", "name": "all", "description": null, "createdAt": 0 },
+            ]),
+        );
+        mock_json(&mut server, "/api/roles/stale_id/scopes", json!([]));
+        mock_json(
+            &mut server,
+            "/api/organization-scopes",
+            json!([{ "id": "stale_scope_id", "name": "stale:scope", "description": null }]),
+        );
+        mock_json(
+            &mut server,
+            "/api/organization-roles",
+            json!([{ "id": "viewer_id", "name": "viewer", "description": null }]),
+        );
+
+        let mut options = ReconcileOptions {
+            prune: true,
+            ..Default::default()
+        };
+
+        let plan = client.plan(&Manifest::default(), options).await.unwrap();
+
+        assert_eq!(
+            plan.to_string(),
+            "- resource https://legacy.example.com\n\
+             - role stale\n\
+             - organization scope stale:scope\n\
+             - organization role viewer\n"
+        );
+
+        options.prune_management_roles = true;
+        let plan = client.plan(&Manifest::default(), options).await.unwrap();
+
+        assert!(plan.to_string().contains("- role m2m admin\n"));
+        assert!(!plan.to_string().contains("https://default.logto.app/api"));
+    }
+}
@@ -0,0 +1,335 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    core::{
+        error::LogtoError,
+        scope::{Scope, Scopes},
+    },
+    management::client::{ManagementClient, Page, Pagination},
+};
+
+/// An API resource, its `indicator` is the `aud` of the access tokens issued
+/// for it, see https://openapi.logto.io/group/endpoint-resources
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub id: String,
+    pub name: String,
+    pub indicator: String,
+    /// Default resources are used when a token request has no `resource`
+    #[serde(default)]
+    pub is_default: bool,
+    /// In seconds
+    pub access_token_ttl: u64,
+    /// Only set when listed with scopes
+    pub scopes: Option<Vec<ResourceScope>>,
+}
+
+impl Resource {
+    /// The scopes as checked in access tokens, empty if not fetched
+    pub fn scope_set(&self) -> Scopes {
+        self.scopes
+            .iter()
+            .flatten()
+            .map(ResourceScope::scope)
+            .collect()
+    }
+}
+
+/// A permission of an API resource, see https://openapi.logto.io/group/endpoint-resources
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceScope {
+    pub id: String,
+    pub resource_id: String,
+    /// The scope value in access tokens, e.g. `read:orders`
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: u64,
+}
+
+impl ResourceScope {
+    pub fn scope(&self) -> Scope {
+        Scope::from(self.name.as_str())
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateResource<'a> {
+    pub name: &'a str,
+    /// Usually the API's base URL, it can't be changed later
+    pub indicator: &'a str,
+    /// In seconds, Logto defaults to an hour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token_ttl: Option<u64>,
+}
+
+/// Only the set fields are updated
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateResource<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token_ttl: Option<u64>,
+}
+
+/// Used both to create and update resource scopes
+#[derive(Debug, Default, Serialize)]
+pub struct ResourceScopeEntry<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+}
+
+impl ManagementClient {
+    /// Lists every API resource, with their scopes if `include_scopes` is set
+    pub async fn list_resources(&self, include_scopes: bool) -> Result<Vec<Resource>, LogtoError> {
+        let request = self.request(Method::GET, &["resources"]).await?;
+
+        self.send(request.query(&[("includeScopes", include_scopes)]))
+            .await
+    }
+
+    pub async fn get_resource(&self, resource_id: &str) -> Result<Resource, LogtoError> {
+        let request = self
+            .request(Method::GET, &["resources", resource_id])
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_resource(
+        &self,
+        resource: CreateResource<'_>,
+    ) -> Result<Resource, LogtoError> {
+        let request = self.request(Method::POST, &["resources"]).await?;
+
+        self.send(request.json(&resource)).await
+    }
+
+    pub async fn update_resource(
+        &self,
+        resource_id: &str,
+        resource: UpdateResource<'_>,
+    ) -> Result<Resource, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["resources", resource_id])
+            .await?;
+
+        self.send(request.json(&resource)).await
+    }
+
+    /// Only one resource can be the default, setting it unsets the others
+    pub async fn set_resource_default(
+        &self,
+        resource_id: &str,
+        is_default: bool,
+    ) -> Result<Resource, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["resources", resource_id, "is-default"])
+            .await?;
+
+        self.send(request.json(&json!({ "isDefault": is_default })))
+            .await
+    }
+
+    pub async fn delete_resource(&self, resource_id: &str) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["resources", resource_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_resource_scopes(
+        &self,
+        resource_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<ResourceScope>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["resources", resource_id, "scopes"])
+            .await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    pub async fn create_resource_scope(
+        &self,
+        resource_id: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<ResourceScope, LogtoError> {
+        let request = self
+            .request(Method::POST, &["resources", resource_id, "scopes"])
+            .await?;
+
+        self.send(request.json(&ResourceScopeEntry {
+            name: Some(name),
+            description,
+        }))
+        .await
+    }
+
+    pub async fn update_resource_scope(
+        &self,
+        resource_id: &str,
+        scope_id: &str,
+        scope: ResourceScopeEntry<'_>,
+    ) -> Result<ResourceScope, LogtoError> {
+        let request = self
+            .request(
+                Method::PATCH,
+                &["resources", resource_id, "scopes", scope_id],
+            )
+            .await?;
+
+        self.send(request.json(&scope)).await
+    }
+
+    pub async fn delete_resource_scope(
+        &self,
+        resource_id: &str,
+        scope_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["resources", resource_id, "scopes", scope_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    /// Returns the scopes in `required` that the resource with `indicator`
+    /// doesn't define, e.g. to check at startup the `required_scopes` given to
+    /// `verify_access_token`. Every scope is missing if there's no such
+    /// resource.
+    pub async fn missing_resource_scopes(
+        &self,
+        indicator: &str,
+        required: &Scopes,
+    ) -> Result<Scopes, LogtoError> {
+        let defined = self
+            .list_resources(true)
+            .await?
+            .into_iter()
+            .find(|resource| resource.indicator == indicator)
+            .map(|resource| resource.scope_set())
+            .unwrap_or_default();
+
+        Ok(defined.missing(required))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const RESOURCE: &str = r#"{
+        "id": "resource_id",
+        "tenantId": "default",
+        "name": "Orders",
+        "indicator": "https://orders.example.com",
+        "isDefault": false,
+        "accessTokenTtl": 3600,
+        "scopes": [{
+            "id": "scope_id",
+            "tenantId": "default",
+            "resourceId": "resource_id",
+            "name": "read:orders",
+            "description": null,
+            "createdAt": 1700000000000
+        }]
+    }"#;
+
+    #[tokio::test]
+    async fn test_create_resource() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/resources")
+            .match_header("authorization", "Bearer management_token")
+            .match_body(Matcher::Json(json!({
+                "name": "Orders",
+                "indicator": "https://orders.example.com",
+                "accessTokenTtl": 3600,
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(RESOURCE.replace(r#""scopes""#, r#""ignored""#))
+            .create();
+
+        let resource = client
+            .create_resource(CreateResource {
+                name: "Orders",
+                indicator: "https://orders.example.com",
+                access_token_ttl: Some(3600),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(resource.access_token_ttl, 3600);
+        assert!(resource.scopes.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_resource_default() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("PATCH", "/api/resources/resource_id/is-default")
+            .match_body(Matcher::Json(json!({ "isDefault": true })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(RESOURCE.replace(r#""isDefault": false"#, r#""isDefault": true"#))
+            .create();
+
+        let resource = client
+            .set_resource_default("resource_id", true)
+            .await
+            .unwrap();
+
+        assert!(resource.is_default);
+    }
+
+    #[tokio::test]
+    async fn test_missing_resource_scopes() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/resources")
+            .match_query(Matcher::UrlEncoded("includeScopes".into(), "true".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!("[{}]", RESOURCE))
+            .create();
+
+        let required: Scopes = "read:orders write:orders".parse().unwrap();
+
+        let missing = client
+            .missing_resource_scopes("https://orders.example.com", &required)
+            .await
+            .unwrap();
+        assert_eq!(missing.to_string(), "write:orders");
+
+        let missing = client
+            .missing_resource_scopes("https://unknown.example.com", &required)
+            .await
+            .unwrap();
+        assert_eq!(missing, required);
+    }
+}
@@ -0,0 +1,417 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    core::error::LogtoError,
+    management::{
+        applications::Application,
+        client::{ManagementClient, Page, Pagination},
+        resources::ResourceScope,
+        users::User,
+    },
+};
+
+/// Who a role can be assigned to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RoleType {
+    #[default]
+    User,
+    MachineToMachine,
+}
+
+impl RoleType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "User",
+            Self::MachineToMachine => "MachineToMachine",
+        }
+    }
+}
+
+/// See https://openapi.logto.io/group/endpoint-roles
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Role {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub role_type: RoleType,
+    /// Default roles are assigned to new users
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ListRolesQuery<'a> {
+    pub role_type: Option<RoleType>,
+    /// Fuzzy search over the name and description
+    pub search: Option<&'a str>,
+    pub pagination: Option<Pagination>,
+}
+
+impl<'a> ListRolesQuery<'a> {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+
+        if let Some(role_type) = self.role_type {
+            query.push(("type", role_type.as_str().to_string()));
+        }
+
+        if let Some(search) = self.search {
+            query.push(("search", format!("%{}%", search)));
+        }
+
+        if let Some(pagination) = self.pagination {
+            query.extend(pagination.to_query());
+        }
+
+        query
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRole<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    #[serde(rename = "type")]
+    pub role_type: RoleType,
+    /// Scopes assigned on creation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scope_ids: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+}
+
+/// Only the set fields are updated, the type of a role can't change
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRole<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+}
+
+impl ManagementClient {
+    pub async fn list_roles(&self, query: ListRolesQuery<'_>) -> Result<Page<Role>, LogtoError> {
+        let request = self.request(Method::GET, &["roles"]).await?;
+
+        self.send_page(request.query(&query.to_query())).await
+    }
+
+    pub async fn get_role(&self, role_id: &str) -> Result<Role, LogtoError> {
+        let request = self.request(Method::GET, &["roles", role_id]).await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_role(&self, role: CreateRole<'_>) -> Result<Role, LogtoError> {
+        let request = self.request(Method::POST, &["roles"]).await?;
+
+        self.send(request.json(&role)).await
+    }
+
+    pub async fn update_role(
+        &self,
+        role_id: &str,
+        role: UpdateRole<'_>,
+    ) -> Result<Role, LogtoError> {
+        let request = self.request(Method::PATCH, &["roles", role_id]).await?;
+
+        self.send(request.json(&role)).await
+    }
+
+    pub async fn delete_role(&self, role_id: &str) -> Result<(), LogtoError> {
+        let request = self.request(Method::DELETE, &["roles", role_id]).await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_role_scopes(&self, role_id: &str) -> Result<Vec<ResourceScope>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["roles", role_id, "scopes"])
+            .await?;
+
+        self.send(request).await
+    }
+
+    /// Returns the newly assigned scopes
+    pub async fn assign_role_scopes(
+        &self,
+        role_id: &str,
+        scope_ids: &[&str],
+    ) -> Result<Vec<ResourceScope>, LogtoError> {
+        let request = self
+            .request(Method::POST, &["roles", role_id, "scopes"])
+            .await?;
+
+        self.send(request.json(&json!({ "scopeIds": scope_ids })))
+            .await
+    }
+
+    pub async fn remove_role_scope(&self, role_id: &str, scope_id: &str) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["roles", role_id, "scopes", scope_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_role_users(
+        &self,
+        role_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<User>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["roles", role_id, "users"])
+            .await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    /// Only for `RoleType::User` roles
+    pub async fn assign_role_to_users(
+        &self,
+        role_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::POST, &["roles", role_id, "users"])
+            .await?;
+
+        self.send_empty(request.json(&json!({ "userIds": user_ids })))
+            .await
+    }
+
+    pub async fn remove_role_from_user(
+        &self,
+        role_id: &str,
+        user_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["roles", role_id, "users", user_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_role_applications(
+        &self,
+        role_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<Application>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["roles", role_id, "applications"])
+            .await?;
+
+        let query = pagination.map(Pagination::to_query).unwrap_or_default();
+
+        self.send_page(request.query(&query)).await
+    }
+
+    /// Only for `RoleType::MachineToMachine` roles
+    pub async fn assign_role_to_applications(
+        &self,
+        role_id: &str,
+        application_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::POST, &["roles", role_id, "applications"])
+            .await?;
+
+        self.send_empty(request.json(&json!({ "applicationIds": application_ids })))
+            .await
+    }
+
+    pub async fn remove_role_from_application(
+        &self,
+        role_id: &str,
+        application_id: &str,
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(
+                Method::DELETE,
+                &["roles", role_id, "applications", application_id],
+            )
+            .await?;
+
+        self.send_empty(request).await
+    }
+
+    pub async fn list_user_roles(&self, user_id: &str) -> Result<Vec<Role>, LogtoError> {
+        let request = self
+            .request(Method::GET, &["users", user_id, "roles"])
+            .await?;
+
+        self.send(request).await
+    }
+
+    pub async fn assign_user_roles(
+        &self,
+        user_id: &str,
+        role_ids: &[&str],
+    ) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::POST, &["users", user_id, "roles"])
+            .await?;
+
+        self.send_empty(request.json(&json!({ "roleIds": role_ids })))
+            .await
+    }
+
+    pub async fn remove_user_role(&self, user_id: &str, role_id: &str) -> Result<(), LogtoError> {
+        let request = self
+            .request(Method::DELETE, &["users", user_id, "roles", role_id])
+            .await?;
+
+        self.send_empty(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const ROLE: &str = r#"{
+        "id": "role_id",
+        "tenantId": "default",
+        "name": "billing-service",
+        "description": "Calls the billing API",
+        "type": "MachineToMachine",
+        "isDefault": false
+    }"#;
+
+    #[tokio::test]
+    async fn test_list_roles() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/roles")
+            .match_header("authorization", "Bearer management_token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("type".into(), "MachineToMachine".into()),
+                Matcher::UrlEncoded("search".into(), "%billing%".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("total-number", "1")
+            .with_body(format!("[{}]", ROLE))
+            .create();
+
+        let page = client
+            .list_roles(ListRolesQuery {
+                role_type: Some(RoleType::MachineToMachine),
+                search: Some("billing"),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(page.total, Some(1));
+        assert_eq!(page.items[0].role_type, RoleType::MachineToMachine);
+    }
+
+    #[tokio::test]
+    async fn test_create_role() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/roles")
+            .match_body(Matcher::Json(json!({
+                "name": "billing-service",
+                "description": "Calls the billing API",
+                "type": "MachineToMachine",
+                "scopeIds": ["scope_id"],
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ROLE)
+            .create();
+
+        let role = client
+            .create_role(CreateRole {
+                name: "billing-service",
+                description: "Calls the billing API",
+                role_type: RoleType::MachineToMachine,
+                scope_ids: vec!["scope_id"],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(role.id, "role_id");
+    }
+
+    #[tokio::test]
+    async fn test_assign_role_scopes_and_applications() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/roles/role_id/scopes")
+            .match_body(Matcher::Json(json!({ "scopeIds": ["scope_id"] })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"[{
+                    "id": "scope_id",
+                    "resourceId": "resource_id",
+                    "name": "read:invoices",
+                    "description": null,
+                    "createdAt": 1700000000000
+                }]"#,
+            )
+            .create();
+        let applications = server
+            .mock("POST", "/api/roles/role_id/applications")
+            .match_body(Matcher::Json(
+                json!({ "applicationIds": ["application_id"] }),
+            ))
+            .with_status(201)
+            .expect(1)
+            .create();
+
+        let scopes = client
+            .assign_role_scopes("role_id", &["scope_id"])
+            .await
+            .unwrap();
+        assert_eq!(scopes[0].name, "read:invoices");
+
+        client
+            .assign_role_to_applications("role_id", &["application_id"])
+            .await
+            .unwrap();
+        applications.assert();
+    }
+
+    #[tokio::test]
+    async fn test_assign_user_roles() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        let mock = server
+            .mock("POST", "/api/users/user_id/roles")
+            .match_body(Matcher::Json(json!({ "roleIds": ["role_id"] })))
+            .with_status(201)
+            .expect(1)
+            .create();
+
+        client
+            .assign_user_roles("user_id", &["role_id"])
+            .await
+            .unwrap();
+        mock.assert();
+    }
+}
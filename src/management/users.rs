@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    core::error::LogtoError,
+    management::client::{ManagementClient, Page, Pagination},
+};
+
+/// See https://openapi.logto.io/operation/operation-getuser
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub id: String,
+    pub username: Option<String>,
+    pub primary_email: Option<String>,
+    pub primary_phone: Option<String>,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub custom_data: Map<String, Value>,
+    /// Social identities keyed by connector target
+    #[serde(default)]
+    pub identities: HashMap<String, Value>,
+    /// Standard OIDC profile claims, e.g. `givenName`
+    #[serde(default)]
+    pub profile: Map<String, Value>,
+    pub application_id: Option<String>,
+    /// Unix timestamps in milliseconds
+    pub last_sign_in_at: Option<u64>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub is_suspended: bool,
+    #[serde(default)]
+    pub has_password: bool,
+}
+
+/// Matching mode of a field search
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SearchMode {
+    /// `%` in the value is a wildcard
+    #[default]
+    Like,
+    Exact,
+}
+
+impl SearchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Like => "like",
+            Self::Exact => "exact",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListUsersQuery<'a> {
+    /// Fuzzy search over id, username, email, phone and name
+    pub search: Option<&'a str>,
+    /// Searches single fields, e.g. `("primaryEmail", "%@example.com")`
+    pub search_fields: Vec<(&'a str, &'a str)>,
+    pub mode: SearchMode,
+    pub is_case_sensitive: bool,
+    pub pagination: Option<Pagination>,
+}
+
+impl<'a> ListUsersQuery<'a> {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+
+        if let Some(search) = self.search {
+            query.push(("search".to_string(), format!("%{}%", search)));
+        }
+
+        for (field, value) in &self.search_fields {
+            query.push((format!("search.{}", field), value.to_string()));
+            query.push((format!("mode.{}", field), self.mode.as_str().to_string()));
+        }
+
+        if self.is_case_sensitive {
+            query.push(("isCaseSensitive".to_string(), "true".to_string()));
+        }
+
+        if let Some(pagination) = self.pagination {
+            query.extend(
+                pagination
+                    .to_query()
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value)),
+            );
+        }
+
+        query
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUser<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_email: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_phone: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_data: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Map<String, Value>>,
+}
+
+/// Only the set fields are updated
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUser<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_email: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_phone: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Map<String, Value>>,
+}
+
+impl ManagementClient {
+    pub async fn list_users(&self, query: ListUsersQuery<'_>) -> Result<Page<User>, LogtoError> {
+        let request = self.request(Method::GET, &["users"]).await?;
+
+        self.send_page(request.query(&query.to_query())).await
+    }
+
+    pub async fn get_user(&self, user_id: &str) -> Result<User, LogtoError> {
+        let request = self.request(Method::GET, &["users", user_id]).await?;
+
+        self.send(request).await
+    }
+
+    pub async fn create_user(&self, user: CreateUser<'_>) -> Result<User, LogtoError> {
+        let request = self.request(Method::POST, &["users"]).await?;
+
+        self.send(request.json(&user)).await
+    }
+
+    pub async fn update_user(
+        &self,
+        user_id: &str,
+        user: UpdateUser<'_>,
+    ) -> Result<User, LogtoError> {
+        let request = self.request(Method::PATCH, &["users", user_id]).await?;
+
+        self.send(request.json(&user)).await
+    }
+
+    /// Merges `custom_data` into the user's custom data and returns the result
+    pub async fn update_user_custom_data(
+        &self,
+        user_id: &str,
+        custom_data: Map<String, Value>,
+    ) -> Result<Map<String, Value>, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["users", user_id, "custom-data"])
+            .await?;
+
+        self.send(request.json(&json!({ "customData": custom_data })))
+            .await
+    }
+
+    pub async fn update_user_password(
+        &self,
+        user_id: &str,
+        password: &str,
+    ) -> Result<User, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["users", user_id, "password"])
+            .await?;
+
+        self.send(request.json(&json!({ "password": password })))
+            .await
+    }
+
+    /// Returns whether `password` is the user's password
+    pub async fn verify_user_password(
+        &self,
+        user_id: &str,
+        password: &str,
+    ) -> Result<bool, LogtoError> {
+        let request = self
+            .request(Method::POST, &["users", user_id, "password", "verify"])
+            .await?;
+
+        match self
+            .send_empty(request.json(&json!({ "password": password })))
+            .await
+        {
+            Ok(()) => Ok(true),
+            Err(LogtoError::Management(e))
+                if e.status == StatusCode::UNPROCESSABLE_ENTITY.as_u16() =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Suspended users can't sign in and their tokens are revoked
+    pub async fn set_user_suspended(
+        &self,
+        user_id: &str,
+        is_suspended: bool,
+    ) -> Result<User, LogtoError> {
+        let request = self
+            .request(Method::PATCH, &["users", user_id, "is-suspended"])
+            .await?;
+
+        self.send(request.json(&json!({ "isSuspended": is_suspended })))
+            .await
+    }
+
+    pub async fn delete_user(&self, user_id: &str) -> Result<(), LogtoError> {
+        let request = self.request(Method::DELETE, &["users", user_id]).await?;
+
+        self.send_empty(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+
+    use super::*;
+    use crate::management::client::tests::management_client;
+
+    const USER: &str = r#"{
+        "id": "user_id",
+        "username": "jane",
+        "primaryEmail": "jane@example.com",
+        "primaryPhone": null,
+        "name": "Jane",
+        "avatar": null,
+        "customData": { "plan": "pro" },
+        "identities": {},
+        "profile": {},
+        "applicationId": null,
+        "lastSignInAt": null,
+        "createdAt": 1700000000000,
+        "updatedAt": 1700000000000,
+        "isSuspended": false,
+        "hasPassword": true
+    }"#;
+
+    #[tokio::test]
+    async fn test_list_users() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("GET", "/api/users")
+            .match_header("authorization", "Bearer management_token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("search.primaryEmail".into(), "%@example.com".into()),
+                Matcher::UrlEncoded("mode.primaryEmail".into(), "like".into()),
+                Matcher::UrlEncoded("page".into(), "2".into()),
+                Matcher::UrlEncoded("page_size".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("total-number", "3")
+            .with_body(format!("[{}]", USER))
+            .create();
+
+        let page = client
+            .list_users(ListUsersQuery {
+                search_fields: vec![("primaryEmail", "%@example.com")],
+                pagination: Some(Pagination {
+                    page: 2,
+                    page_size: 1,
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(page.total, Some(3));
+        assert_eq!(page.items[0].username.as_deref(), Some("jane"));
+        assert_eq!(page.items[0].custom_data["plan"], "pro");
+    }
+
+    #[tokio::test]
+    async fn test_create_user() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/users")
+            .match_body(Matcher::Json(json!({
+                "username": "jane",
+                "password": "s3cret-Passw0rd",
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(USER)
+            .create();
+
+        let user = client
+            .create_user(CreateUser {
+                username: Some("jane"),
+                password: Some("s3cret-Passw0rd"),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(user.id, "user_id");
+        assert!(user.has_password);
+    }
+
+    #[tokio::test]
+    async fn test_verify_user_password() {
+        let mut server = mockito::Server::new();
+        let client = management_client(&mut server);
+
+        server
+            .mock("POST", "/api/users/user_id/password/verify")
+            .match_body(Matcher::Json(json!({ "password": "right" })))
+            .with_status(204)
+            .create();
+        server
+            .mock("POST", "/api/users/user_id/password/verify")
+            .match_body(Matcher::Json(json!({ "password": "wrong" })))
+            .with_status(422)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"code": "session.invalid_credentials", "message": "Wrong password"}"#)
+            .create();
+
+        assert!(client
+            .verify_user_password("user_id", "right")
+            .await
+            .unwrap());
+        assert!(!client
+            .verify_user_password("user_id", "wrong")
+            .await
+            .unwrap());
+    }
+}
@@ -17,22 +17,39 @@ pub struct IdTokenClaims {
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub avatar: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organizations: Option<Vec<String>>,
+    /// Formatted as `{organization_id}:{role_name}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_roles: Option<Vec<String>>,
 }
 
 impl IdTokenClaims {
+    /// Roles the user has in the given organization, requires the
+    /// `urn:logto:scope:organization_roles` scope
+    pub fn roles_in_organization(&self, organization_id: &str) -> Vec<String> {
+        self.organization_roles
+            .iter()
+            .flatten()
+            .filter_map(|role| role.split_once(':'))
+            .filter(|(id, _)| *id == organization_id)
+            .map(|(_, role)| role.to_string())
+            .collect()
+    }
+
     pub fn to_payload(&self) -> JwtPayload {
         let value = serde_json::to_value(self).unwrap();
         JwtPayload::from_map(value.as_object().unwrap().clone()).unwrap()
     }
 }
 
-fn decode_id_token(token: &str) -> Result<IdTokenClaims, Error> {
+pub fn decode_id_token(token: &str) -> Result<IdTokenClaims, Error> {
     let key = DecodingKey::from_secret(&[]);
     let mut validation = Validation::new(Algorithm::RS256);
     validation.validate_aud = false;
     validation.insecure_disable_signature_validation();
 
-    match decode::<IdTokenClaims>(&token, &key, &validation) {
+    match decode::<IdTokenClaims>(token, &key, &validation) {
         Ok(decoded_token) => Ok(decoded_token.claims),
         Err(e) => Err(e),
     }
@@ -68,6 +85,8 @@ mod tests {
             username: None,
             name: None,
             avatar: None,
+            organizations: None,
+            organization_roles: None,
         };
 
         let token_str = encode(&header, &expected_claims, &key);
@@ -94,4 +113,31 @@ mod tests {
     fn fail_decode_valid_jwt_wrong_payload() {
         assert!(decode_id_token("part1.invalidPayload.part3").is_err())
     }
+
+    #[test]
+    fn roles_in_organization() {
+        let claims = IdTokenClaims {
+            sub: "bar".to_string(),
+            iss: "foo".to_string(),
+            aud: "qux".to_string(),
+            exp: 0,
+            iat: 0,
+            at_hash: None,
+            username: None,
+            name: None,
+            avatar: None,
+            organizations: Some(vec!["org1".to_string(), "org2".to_string()]),
+            organization_roles: Some(vec![
+                "org1:admin".to_string(),
+                "org2:member".to_string(),
+                "org1:billing".to_string(),
+            ]),
+        };
+
+        assert_eq!(
+            claims.roles_in_organization("org1"),
+            vec!["admin", "billing"]
+        );
+        assert!(claims.roles_in_organization("org3").is_empty());
+    }
 }
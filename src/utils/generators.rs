@@ -2,23 +2,23 @@ use base64::{engine::general_purpose, Engine as _};
 use rand::distributions::{Alphanumeric, DistString};
 use sha2::{Digest, Sha256};
 
-fn generate_code_verifier() -> String {
+pub fn generate_code_verifier() -> String {
     generate_random_string()
 }
 
-fn generate_code_challenge(code_verifier: String) -> String {
+pub fn generate_code_challenge(code_verifier: String) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(&code_verifier.as_bytes());
+    hasher.update(code_verifier.as_bytes());
     let result = hasher.finalize();
 
-    general_purpose::URL_SAFE_NO_PAD.encode(&result)
+    general_purpose::URL_SAFE_NO_PAD.encode(result)
 }
 
-fn generate_state() -> String {
+pub fn generate_state() -> String {
     generate_random_string()
 }
 
-fn generate_random_string() -> String {
+pub(crate) fn generate_random_string() -> String {
     general_purpose::URL_SAFE_NO_PAD.encode(Alphanumeric.sample_string(&mut rand::thread_rng(), 64))
 }
 
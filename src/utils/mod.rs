@@ -1,4 +1,6 @@
-mod decode_id_token;
-mod generators;
-mod verify_and_parse_code_from_callback_uri;
-mod verify_id_token;
+pub mod decode_id_token;
+pub mod generators;
+pub mod verify_access_token;
+pub mod verify_and_parse_code_from_callback_uri;
+pub mod verify_id_token;
+pub mod verify_logout_token;
@@ -0,0 +1,496 @@
+use std::{fmt, time::Duration};
+
+use base64::{engine::general_purpose, Engine as _};
+use jsonwebtoken::{
+    decode, decode_header,
+    errors::{Error, ErrorKind},
+    jwk::{AlgorithmParameters, Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    core::{
+        access_token::now,
+        dpop::{access_token_hash, htu, DpopProofClaims, DPOP_PROOF_TYPE},
+        scope::Scopes,
+    },
+    utils::verify_logout_token::JtiReplayCache,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccessTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub exp: u64,
+    pub iat: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Scopes::is_empty")]
+    pub scope: Scopes,
+    /// Set on tokens issued through token exchange with an actor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+    /// Set on DPoP-bound tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cnf: Option<Confirmation>,
+}
+
+/// Thumbprint of the key a DPoP-bound token is bound to,
+/// see https://datatracker.ietf.org/doc/html/rfc9449#section-6.1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Confirmation {
+    pub jkt: String,
+}
+
+/// The party acting on behalf of the subject, nested for delegation chains,
+/// see https://datatracker.ietf.org/doc/html/rfc8693#section-4.1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<ActorClaim>>,
+}
+
+pub struct AccessTokenInfoParameters<'a> {
+    pub access_token: String,
+    /// The API resource indicator the token must be issued for
+    pub audience: String,
+    pub issuer: String,
+    pub jwks: JwkSet,
+    pub required_scopes: Option<Scopes>,
+    /// The request's `DPoP` proof, required when the token is DPoP-bound
+    pub dpop: Option<DpopProofParameters<'a>>,
+}
+
+#[derive(Debug)]
+pub enum AccessTokenError {
+    Jwt(Error),
+    InsufficientScope(Scopes),
+    InvalidDpopProof(&'static str),
+}
+
+impl fmt::Display for AccessTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jwt(e) => write!(f, "invalid access token: {}", e),
+            Self::InsufficientScope(missing) => write!(f, "missing scopes: {}", missing),
+            Self::InvalidDpopProof(reason) => write!(f, "invalid DPoP proof: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AccessTokenError {}
+
+impl From<Error> for AccessTokenError {
+    fn from(e: Error) -> Self {
+        Self::Jwt(e)
+    }
+}
+
+/// Verifies a JWT access token issued by Logto for an API resource and
+/// returns its claims, see https://docs.logto.io/docs/recipes/protect-your-api/
+/// DPoP-bound tokens (with `cnf.jkt`) are only accepted along a valid proof.
+pub fn verify_access_token(
+    params: AccessTokenInfoParameters,
+) -> Result<AccessTokenClaims, AccessTokenError> {
+    let header = decode_header(params.access_token.as_str())?;
+
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(Error::from(ErrorKind::InvalidAlgorithm).into());
+    }
+
+    let kid = header
+        .kid
+        .ok_or_else(|| Error::from(ErrorKind::InvalidToken))?;
+
+    let jwk = params
+        .jwks
+        .find(&kid)
+        .ok_or_else(|| Error::from(ErrorKind::InvalidSignature))?;
+
+    let decoding_key = DecodingKey::from_jwk(jwk)?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[params.audience.as_str()]);
+    validation.set_issuer(&[params.issuer.as_str()]);
+
+    let claims =
+        decode::<AccessTokenClaims>(&params.access_token, &decoding_key, &validation)?.claims;
+
+    if let Some(required_scopes) = &params.required_scopes {
+        let missing = claims.scope.missing(required_scopes);
+
+        if !missing.is_empty() {
+            return Err(AccessTokenError::InsufficientScope(missing));
+        }
+    }
+
+    match (&claims.cnf, &params.dpop) {
+        (_, Some(dpop)) => verify_dpop_proof(dpop, &params.access_token, &claims)?,
+        (Some(_), None) => {
+            return Err(AccessTokenError::InvalidDpopProof(
+                "missing proof for a DPoP-bound token",
+            ))
+        }
+        (None, None) => {}
+    }
+
+    Ok(claims)
+}
+
+pub struct DpopProofParameters<'a> {
+    /// Value of the request's `DPoP` header
+    pub proof: &'a str,
+    /// Method and URI of the request the proof was sent with
+    pub method: &'a str,
+    pub uri: &'a str,
+    /// The nonce the server expects, if it hands out nonces
+    pub nonce: Option<&'a str>,
+    /// How far `iat` may be from now
+    pub max_age: Duration,
+    /// Rejects proofs used before
+    pub replay_cache: &'a JtiReplayCache,
+}
+
+// https://datatracker.ietf.org/doc/html/rfc9449#section-4.3
+fn verify_dpop_proof(
+    params: &DpopProofParameters,
+    access_token: &str,
+    access_token_claims: &AccessTokenClaims,
+) -> Result<(), AccessTokenError> {
+    let header = decode_header(params.proof)?;
+
+    if header.typ.as_deref() != Some(DPOP_PROOF_TYPE) {
+        return Err(AccessTokenError::InvalidDpopProof("wrong typ"));
+    }
+
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(Error::from(ErrorKind::InvalidAlgorithm).into());
+    }
+
+    let jwk = header
+        .jwk
+        .ok_or(AccessTokenError::InvalidDpopProof("missing jwk"))?;
+
+    if has_private_key(params.proof) {
+        return Err(AccessTokenError::InvalidDpopProof(
+            "jwk contains a private key",
+        ));
+    }
+
+    let mut validation = Validation::new(header.alg);
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+
+    let claims =
+        decode::<DpopProofClaims>(params.proof, &DecodingKey::from_jwk(&jwk)?, &validation)?.claims;
+
+    if claims.htm != params.method {
+        return Err(AccessTokenError::InvalidDpopProof("htm mismatch"));
+    }
+
+    if htu(&claims.htu) != htu(params.uri) {
+        return Err(AccessTokenError::InvalidDpopProof("htu mismatch"));
+    }
+
+    if now().abs_diff(claims.iat) > params.max_age.as_secs() {
+        return Err(AccessTokenError::InvalidDpopProof("iat out of range"));
+    }
+
+    if params.nonce.is_some() && claims.nonce.as_deref() != params.nonce {
+        return Err(AccessTokenError::InvalidDpopProof("nonce mismatch"));
+    }
+
+    if claims.ath.as_deref() != Some(access_token_hash(access_token).as_str()) {
+        return Err(AccessTokenError::InvalidDpopProof("ath mismatch"));
+    }
+
+    match &access_token_claims.cnf {
+        Some(cnf) if Some(&cnf.jkt) == jwk_thumbprint(&jwk).as_ref() => {}
+        _ => {
+            return Err(AccessTokenError::InvalidDpopProof(
+                "key doesn't match cnf.jkt",
+            ))
+        }
+    }
+
+    // Checked last so a rejected proof doesn't use up its jti
+    if !params
+        .replay_cache
+        .insert(&claims.jti, claims.iat + params.max_age.as_secs())
+    {
+        return Err(AccessTokenError::InvalidDpopProof("jti already used"));
+    }
+
+    Ok(())
+}
+
+// The decoded `Jwk` drops private members, so check the raw header
+fn has_private_key(proof: &str) -> bool {
+    let header = proof
+        .split('.')
+        .next()
+        .and_then(|header| general_purpose::URL_SAFE_NO_PAD.decode(header).ok())
+        .and_then(|header| serde_json::from_slice::<serde_json::Value>(&header).ok());
+
+    match header {
+        Some(header) => header["jwk"].get("d").is_some(),
+        None => true,
+    }
+}
+
+// https://datatracker.ietf.org/doc/html/rfc7638#section-3
+fn jwk_thumbprint(jwk: &Jwk) -> Option<String> {
+    let curve = |curve| {
+        serde_json::to_value(curve)
+            .ok()?
+            .as_str()
+            .map(str::to_string)
+    };
+
+    let members = match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(p) => format!(
+            r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+            curve(&p.curve)?,
+            p.x,
+            p.y
+        ),
+        AlgorithmParameters::RSA(p) => format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, p.e, p.n),
+        AlgorithmParameters::OctetKeyPair(p) => format!(
+            r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+            curve(&p.curve)?,
+            p.x
+        ),
+        AlgorithmParameters::OctetKey(_) => return None,
+    };
+
+    Some(general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(members.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use josekit::{
+        jwk::{Jwk, JwkSet},
+        jws::{alg::ecdsa::EcdsaJwsAlgorithm::Es384, JwsHeader},
+        jwt::JwtPayload,
+    };
+
+    use super::*;
+    use crate::core::dpop::DpopKey;
+
+    fn sign_access_token(claims: &AccessTokenClaims) -> (String, JwkSet) {
+        let key_pair = Es384
+            .generate_key_pair()
+            .expect("couldn't generate key pair");
+
+        let mut jwk_keypair: Jwk = key_pair.to_jwk_key_pair();
+        jwk_keypair.set_key_id("123");
+
+        let mut jwk_public: Jwk = jwk_keypair.to_public_key().unwrap();
+        jwk_public.set_key_id("123");
+        jwk_public.set_algorithm("ES384");
+
+        let token_signer = Es384.signer_from_jwk(&jwk_keypair).unwrap();
+
+        let mut header = JwsHeader::new();
+        header.set_key_id("123");
+        header.set_algorithm("ES384");
+
+        let value = serde_json::to_value(claims).unwrap();
+        let payload = JwtPayload::from_map(value.as_object().unwrap().clone()).unwrap();
+
+        let token = josekit::jwt::encode_with_signer(&payload, &header, &token_signer).unwrap();
+
+        let mut initial_map: josekit::Map<String, josekit::Value> = josekit::Map::new();
+        initial_map.insert(
+            "keys".to_string(),
+            josekit::Value::from(Vec::<String>::new()),
+        );
+
+        let mut set = JwkSet::from_map(initial_map).unwrap();
+        set.push_key(jwk_public);
+
+        (token, set)
+    }
+
+    fn claims_with_scope(scope: &str) -> AccessTokenClaims {
+        let since_the_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards");
+
+        AccessTokenClaims {
+            iss: "https://logto.dev/oidc".to_string(),
+            sub: "user".to_string(),
+            aud: "https://api.example.com".to_string(),
+            exp: (since_the_epoch + Duration::from_secs(3600)).as_secs(),
+            iat: since_the_epoch.as_secs(),
+            jti: Some("jti".to_string()),
+            client_id: Some("client".to_string()),
+            scope: scope.parse().unwrap(),
+            act: None,
+            cnf: None,
+        }
+    }
+
+    fn params<'a>(
+        token: String,
+        jwks: &JwkSet,
+        required_scopes: &str,
+    ) -> AccessTokenInfoParameters<'a> {
+        AccessTokenInfoParameters {
+            access_token: token,
+            audience: "https://api.example.com".to_string(),
+            issuer: "https://logto.dev/oidc".to_string(),
+            jwks: serde_json::from_str(&jwks.to_string()).unwrap(),
+            required_scopes: Some(required_scopes.parse().unwrap()),
+            dpop: None,
+        }
+    }
+
+    #[test]
+    fn verify_access_token_works() {
+        let claims = claims_with_scope("read:orders write:orders");
+        let (token, jwks) = sign_access_token(&claims);
+
+        let result = verify_access_token(params(token, &jwks, "read:orders"));
+
+        match result {
+            Ok(verified) => assert_eq!(verified, claims),
+            Err(e) => panic!("Error in verify_access_token: {}", e),
+        }
+    }
+
+    #[test]
+    fn fail_verify_access_token_missing_scopes() {
+        let claims = claims_with_scope("read:orders");
+        let (token, jwks) = sign_access_token(&claims);
+
+        let result = verify_access_token(params(token, &jwks, "read:orders write:orders"));
+
+        match result {
+            Err(AccessTokenError::InsufficientScope(missing)) => {
+                assert_eq!(missing.to_string(), "write:orders")
+            }
+            _ => panic!("Expected insufficient scope error"),
+        }
+    }
+
+    #[test]
+    fn fail_verify_access_token_wrong_audience() {
+        let mut claims = claims_with_scope("read:orders");
+        claims.aud = "https://other.example.com".to_string();
+        let (token, jwks) = sign_access_token(&claims);
+
+        assert!(verify_access_token(params(token, &jwks, "")).is_err())
+    }
+
+    fn bind_to(claims: &mut AccessTokenClaims, key: &DpopKey) {
+        let proof = key
+            .proof("GET", "https://api.example.com", None, None)
+            .unwrap();
+        let jwk = decode_header(&proof).unwrap().jwk.unwrap();
+
+        claims.cnf = Some(Confirmation {
+            jkt: jwk_thumbprint(&jwk).unwrap(),
+        });
+    }
+
+    #[test]
+    fn verify_dpop_bound_access_token() {
+        let key = DpopKey::generate().unwrap();
+        let mut claims = claims_with_scope("read:orders");
+        bind_to(&mut claims, &key);
+        let (token, jwks) = sign_access_token(&claims);
+
+        let uri = "https://api.example.com/orders";
+        let replay_cache = JtiReplayCache::new();
+        let verify = |method, proof| {
+            verify_access_token(AccessTokenInfoParameters {
+                dpop: Some(DpopProofParameters {
+                    proof,
+                    method,
+                    uri,
+                    nonce: None,
+                    max_age: Duration::from_secs(60),
+                    replay_cache: &replay_cache,
+                }),
+                ..params(token.clone(), &jwks, "read:orders")
+            })
+        };
+
+        let proof = key.proof("GET", uri, Some(&token), None).unwrap();
+        assert!(matches!(
+            verify("POST", &proof),
+            Err(AccessTokenError::InvalidDpopProof("htm mismatch"))
+        ));
+        assert_eq!(verify("GET", &proof).unwrap(), claims);
+        assert!(matches!(
+            verify("GET", &proof),
+            Err(AccessTokenError::InvalidDpopProof("jti already used"))
+        ));
+
+        let other_token_proof = key.proof("GET", uri, Some("access_token"), None).unwrap();
+        assert!(matches!(
+            verify("GET", &other_token_proof),
+            Err(AccessTokenError::InvalidDpopProof("ath mismatch"))
+        ));
+    }
+
+    #[test]
+    fn fail_verify_dpop_bound_access_token_without_proof() {
+        let mut claims = claims_with_scope("read:orders");
+        bind_to(&mut claims, &DpopKey::generate().unwrap());
+        let (token, jwks) = sign_access_token(&claims);
+
+        assert!(matches!(
+            verify_access_token(params(token, &jwks, "read:orders")),
+            Err(AccessTokenError::InvalidDpopProof(
+                "missing proof for a DPoP-bound token"
+            ))
+        ));
+    }
+
+    #[test]
+    fn fail_verify_dpop_proof_other_key() {
+        let key = DpopKey::generate().unwrap();
+        let mut claims = claims_with_scope("read:orders");
+        bind_to(&mut claims, &DpopKey::generate().unwrap());
+        let (token, jwks) = sign_access_token(&claims);
+
+        let uri = "https://api.example.com/orders";
+        let proof = key.proof("GET", uri, Some(&token), None).unwrap();
+        let replay_cache = JtiReplayCache::new();
+
+        let result = verify_access_token(AccessTokenInfoParameters {
+            dpop: Some(DpopProofParameters {
+                proof: &proof,
+                method: "GET",
+                uri,
+                nonce: None,
+                max_age: Duration::from_secs(60),
+                replay_cache: &replay_cache,
+            }),
+            ..params(token.clone(), &jwks, "read:orders")
+        });
+
+        assert!(matches!(
+            result,
+            Err(AccessTokenError::InvalidDpopProof(
+                "key doesn't match cnf.jkt"
+            ))
+        ));
+    }
+}
@@ -1,36 +1,142 @@
-use std::{collections::HashMap, error::Error};
+use std::{collections::HashMap, error::Error, fmt};
 
 use reqwest::Url;
+use url::form_urlencoded;
 
-struct UnverifiedUris {
-    callback_uri: String,
-    redirect_uri: String,
-    state: String,
+pub struct UnverifiedUris {
+    pub callback_uri: String,
+    pub redirect_uri: String,
+    pub state: String,
 }
 
-fn verify_and_parse_code_from_callback_uri(
+#[derive(Debug, PartialEq)]
+pub enum AuthorizationErrorCode {
+    AccessDenied,
+    LoginRequired,
+    ConsentRequired,
+    InteractionRequired,
+    Other(String),
+}
+
+impl AuthorizationErrorCode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::AccessDenied => "access_denied",
+            Self::LoginRequired => "login_required",
+            Self::ConsentRequired => "consent_required",
+            Self::InteractionRequired => "interaction_required",
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl From<&str> for AuthorizationErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "access_denied" => Self::AccessDenied,
+            "login_required" => Self::LoginRequired,
+            "consent_required" => Self::ConsentRequired,
+            "interaction_required" => Self::InteractionRequired,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// Error returned by the authorization server through the callback URI,
+/// see https://openid.net/specs/openid-connect-core-1_0.html#AuthError
+#[derive(Debug, PartialEq)]
+pub struct AuthorizationError {
+    pub error: AuthorizationErrorCode,
+    pub error_description: Option<String>,
+    pub error_uri: Option<String>,
+}
+
+impl fmt::Display for AuthorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uri contains error: {}", self.error.as_str())?;
+
+        if let Some(description) = &self.error_description {
+            write!(f, " ({})", description)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for AuthorizationError {}
+
+pub struct UnverifiedFormPost {
+    pub body: String,
+    pub state: String,
+}
+
+pub fn verify_and_parse_code_from_callback_uri(
+    params: UnverifiedUris,
+) -> Result<String, Box<dyn Error>> {
+    verify_redirect_uri(&params)?;
+
+    let parsed_uri = Url::parse(&params.callback_uri)?;
+    let raw_params: HashMap<String, String> = parsed_uri.query_pairs().into_owned().collect();
+
+    parse_code_from_params(&raw_params, &params.state)
+}
+
+/// Same as `verify_and_parse_code_from_callback_uri`, for callbacks made with
+/// `response_mode=fragment`.
+pub fn verify_and_parse_code_from_callback_fragment(
     params: UnverifiedUris,
 ) -> Result<String, Box<dyn Error>> {
+    verify_redirect_uri(&params)?;
+
+    let parsed_uri = Url::parse(&params.callback_uri)?;
+    let raw_params: HashMap<String, String> =
+        form_urlencoded::parse(parsed_uri.fragment().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+
+    parse_code_from_params(&raw_params, &params.state)
+}
+
+/// Same as `verify_and_parse_code_from_callback_uri`, for the URL-encoded body
+/// POSTed to the redirect URI with `response_mode=form_post`.
+pub fn verify_and_parse_code_from_form_post(
+    params: UnverifiedFormPost,
+) -> Result<String, Box<dyn Error>> {
+    let raw_params: HashMap<String, String> = form_urlencoded::parse(params.body.as_bytes())
+        .into_owned()
+        .collect();
+
+    parse_code_from_params(&raw_params, &params.state)
+}
+
+fn verify_redirect_uri(params: &UnverifiedUris) -> Result<(), Box<dyn Error>> {
     if !params.callback_uri.starts_with(&params.redirect_uri) {
-        return Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
+        return Err(Box::new(std::io::Error::other(
             "Callback URI does not start with redirect URI",
         )));
     }
 
-    let parsed_uri = Url::parse(&params.callback_uri)?;
-    let raw_params: HashMap<String, String> = parsed_uri.query_pairs().into_owned().collect();
+    Ok(())
+}
 
+fn parse_code_from_params(
+    raw_params: &HashMap<String, String>,
+    state: &str,
+) -> Result<String, Box<dyn Error>> {
     let query_params: HashMap<&str, &str> = raw_params
         .iter()
         .map(|(a, b)| (a.as_str(), b.as_str()))
         .collect();
 
-    if query_params.contains_key("error") {
-        return Err("uri contains error".into());
+    if let Some(error) = query_params.get("error") {
+        return Err(Box::new(AuthorizationError {
+            error: AuthorizationErrorCode::from(*error),
+            error_description: query_params.get("error_description").map(|d| d.to_string()),
+            error_uri: query_params.get("error_uri").map(|u| u.to_string()),
+        }));
     }
 
-    if !query_params.contains_key("state") || query_params.get("state").unwrap().ne(&params.state) {
+    if !query_params.contains_key("state") || query_params.get("state").unwrap().ne(&state) {
         return Err("states don't match".into());
     }
 
@@ -92,7 +198,33 @@ mod tests {
 
         match result {
             Ok(_) => panic!("Expected error but got ok"),
-            Err(e) => assert_eq!(&*e.to_string(), "uri contains error"),
+            Err(e) => assert_eq!(&*e.to_string(), "uri contains error: some_error"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn callback_uri_with_access_denied() -> Result<()> {
+        let params = UnverifiedUris {
+            callback_uri: "http://example.com/callback?state=123456&error=access_denied&error_description=User%20cancelled&error_uri=https%3A%2F%2Flogto.io%2Ferrors"
+                .to_string(),
+            redirect_uri: "http://example.com/callback".to_string(),
+            state: "123456".to_string(),
+        };
+
+        let result = verify_and_parse_code_from_callback_uri(params);
+
+        match result {
+            Ok(_) => panic!("Expected error but got ok"),
+            Err(e) => assert_eq!(
+                e.downcast_ref::<AuthorizationError>(),
+                Some(&AuthorizationError {
+                    error: AuthorizationErrorCode::AccessDenied,
+                    error_description: Some("User cancelled".to_string()),
+                    error_uri: Some("https://logto.io/errors".to_string()),
+                })
+            ),
         }
 
         Ok(())
@@ -151,4 +283,68 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_verify_and_parse_code_from_callback_fragment() -> Result<()> {
+        let params = UnverifiedUris {
+            callback_uri: "http://example.com/callback#state=123456&code=abcdef".to_string(),
+            redirect_uri: "http://example.com/callback".to_string(),
+            state: "123456".to_string(),
+        };
+
+        let result = verify_and_parse_code_from_callback_fragment(params).unwrap_or_default();
+
+        assert_eq!(result, "abcdef");
+
+        Ok(())
+    }
+
+    #[test]
+    fn callback_fragment_without_code() -> Result<()> {
+        let params = UnverifiedUris {
+            callback_uri: "http://example.com/callback?code=abcdef#state=123456".to_string(),
+            redirect_uri: "http://example.com/callback".to_string(),
+            state: "123456".to_string(),
+        };
+
+        let result = verify_and_parse_code_from_callback_fragment(params);
+
+        match result {
+            Ok(_) => panic!("Expected error but got ok"),
+            Err(e) => assert_eq!(&*e.to_string(), "code parameter is missing"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_and_parse_code_from_form_post() -> Result<()> {
+        let params = UnverifiedFormPost {
+            body: "state=123456&code=abc%2Bdef".to_string(),
+            state: "123456".to_string(),
+        };
+
+        let result = verify_and_parse_code_from_form_post(params).unwrap_or_default();
+
+        assert_eq!(result, "abc+def");
+
+        Ok(())
+    }
+
+    #[test]
+    fn form_post_with_mismatched_state() -> Result<()> {
+        let params = UnverifiedFormPost {
+            body: "state=wrong&code=abcdef".to_string(),
+            state: "123456".to_string(),
+        };
+
+        let result = verify_and_parse_code_from_form_post(params);
+
+        match result {
+            Ok(_) => panic!("Expected error but got ok"),
+            Err(e) => assert_eq!(&*e.to_string(), "states don't match"),
+        }
+
+        Ok(())
+    }
 }
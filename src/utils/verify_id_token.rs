@@ -1,66 +1,68 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
 use jsonwebtoken::{
     decode, decode_header,
     errors::ErrorKind,
     errors::Result,
     jwk::{AlgorithmParameters, JwkSet},
-    Algorithm, DecodingKey, Validation,
+    DecodingKey, Validation,
 };
+use serde::de::DeserializeOwned;
+
+use crate::{core::access_token::now, utils::decode_id_token::IdTokenClaims};
 
-use crate::utils::decode_id_token::IdTokenClaims;
+/// Tolerated clock skew on `iat`, in seconds
+const IAT_LEEWAY: u128 = 60;
 
-struct TokenInfoParameters {
-    id_token: String,
-    client_id: String,
-    issuer: String,
-    jwks: JwkSet,
+pub struct TokenInfoParameters {
+    pub id_token: String,
+    pub client_id: String,
+    pub issuer: String,
+    pub jwks: JwkSet,
 }
 
-fn verify_id_token(params: TokenInfoParameters) -> Result<()> {
-    let header = decode_header(params.id_token.as_str())?;
+/// Verifies an ID token against the tenant's `jwks`, `issuer` and
+/// `client_id` and returns its claims. Logout tokens go through the same
+/// checks, see `verify_logout_token`.
+pub fn verify_id_token(params: TokenInfoParameters) -> Result<IdTokenClaims> {
+    let claims = decode_with_jwks::<IdTokenClaims>(
+        &params.id_token,
+        &params.client_id,
+        &params.issuer,
+        &params.jwks,
+    )?;
+
+    let now = now() as u128;
+
+    if claims.iat > now + IAT_LEEWAY || claims.iat + IAT_LEEWAY < now {
+        return Err(ErrorKind::ExpiredSignature.into());
+    }
 
-    let kid = match header.kid {
-        Some(k) => k,
-        None => return Err(ErrorKind::InvalidToken.into()),
-    };
+    Ok(claims)
+}
 
-    if let Some(j) = params.jwks.find(&kid) {
-        match &j.algorithm {
-            AlgorithmParameters::RSA(rsa) => {
-                let decoding_key = DecodingKey::from_rsa_components(&rsa.n, &rsa.e).unwrap();
-
-                let mut validation = Validation::new(Algorithm::RS256);
-
-                validation.set_audience(&[params.client_id.as_str()]);
-                validation.set_issuer(&[params.issuer.as_str()]);
-
-                match decode::<IdTokenClaims>(&params.id_token, &decoding_key, &validation) {
-                    Ok(token) => {
-                        let start = SystemTime::now();
-                        let since_the_epoch = start
-                            .duration_since(UNIX_EPOCH)
-                            .expect("Time went backwards");
-
-                        match token.claims.iat
-                            > (since_the_epoch + Duration::from_secs(60)).as_millis()
-                            || token.claims.iat
-                                < (since_the_epoch - Duration::from_secs(60)).as_millis()
-                        {
-                            true => {
-                                return Err(ErrorKind::ExpiredSignature.into());
-                            }
-                            false => Ok(()),
-                        }
-                    }
-                    Err(_) => return Err(ErrorKind::InvalidSignature.into()),
-                }
-            }
-            _ => unreachable!("This should be a RSA"),
-        }
-    } else {
-        return Err(ErrorKind::InvalidSignature.into());
+/// Verifies a token signed by one of the tenant's keys in `jwks` and issued by
+/// `issuer` for `audience`, and decodes its claims
+pub(crate) fn decode_with_jwks<T: DeserializeOwned>(
+    token: &str,
+    audience: &str,
+    issuer: &str,
+    jwks: &JwkSet,
+) -> Result<T> {
+    let header = decode_header(token)?;
+
+    let kid = header.kid.ok_or(ErrorKind::InvalidToken)?;
+    let jwk = jwks.find(&kid).ok_or(ErrorKind::InvalidSignature)?;
+
+    if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+        return Err(ErrorKind::InvalidAlgorithm.into());
     }
+
+    let decoding_key = DecodingKey::from_jwk(jwk)?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[audience]);
+    validation.set_issuer(&[issuer]);
+
+    Ok(decode::<T>(token, &decoding_key, &validation)?.claims)
 }
 
 // TODO: Add more test cases
@@ -112,12 +114,14 @@ mod tests {
             sub: "bar".to_string(),
             iss: "foo".to_string(),
             aud: "qux".to_string(),
-            exp: (since_the_epoch + Duration::from_millis(2000)).as_millis(),
-            iat: (since_the_epoch + Duration::from_millis(100)).as_millis(),
+            exp: (since_the_epoch + Duration::from_secs(2)).as_secs() as u128,
+            iat: since_the_epoch.as_secs() as u128,
             at_hash: None,
             username: None,
             name: None,
             avatar: None,
+            organizations: None,
+            organization_roles: None,
         };
 
         let token =
@@ -132,12 +136,14 @@ mod tests {
         let mut set = JwkSet::from_map(initial_map).unwrap();
         set.push_key(jwk_public);
 
-        assert!(verify_id_token(TokenInfoParameters {
+        let verified = verify_id_token(TokenInfoParameters {
             id_token: token.to_string(),
             client_id: "qux".to_string(),
             issuer: "foo".to_string(),
-            jwks: serde_json::from_str(&set.to_string()).unwrap()
+            jwks: serde_json::from_str(&set.to_string()).unwrap(),
         })
-        .is_ok())
+        .unwrap();
+
+        assert_eq!(verified, claims);
     }
 }
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, fmt, sync::Mutex};
+
+use jsonwebtoken::{errors::Error, jwk::JwkSet};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{core::access_token::now, utils::verify_id_token::decode_with_jwks};
+
+pub const BACK_CHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// Claims of a back-channel logout token,
+/// see https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogoutTokenClaims {
+    pub iss: String,
+    pub aud: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: String,
+    pub events: HashMap<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Only here to reject tokens carrying it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+pub struct LogoutTokenParameters<'a> {
+    pub logout_token: &'a str,
+    pub client_id: &'a str,
+    pub issuer: &'a str,
+    pub jwks: &'a JwkSet,
+    pub replay_cache: &'a JtiReplayCache,
+}
+
+#[derive(Debug)]
+pub enum LogoutTokenError {
+    Jwt(Error),
+    MissingLogoutEvent,
+    MissingSidAndSub,
+    NonceNotAllowed,
+    Replayed,
+}
+
+impl fmt::Display for LogoutTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jwt(e) => write!(f, "invalid logout token: {}", e),
+            Self::MissingLogoutEvent => {
+                f.write_str("logout token has no back-channel logout event")
+            }
+            Self::MissingSidAndSub => f.write_str("logout token has neither sid nor sub"),
+            Self::NonceNotAllowed => f.write_str("logout token must not have a nonce"),
+            Self::Replayed => f.write_str("logout token was already used"),
+        }
+    }
+}
+
+impl std::error::Error for LogoutTokenError {}
+
+impl From<Error> for LogoutTokenError {
+    fn from(e: Error) -> Self {
+        Self::Jwt(e)
+    }
+}
+
+/// Remembers the `jti` of accepted logout tokens until they expire
+#[derive(Default)]
+pub struct JtiReplayCache {
+    seen: Mutex<HashMap<String, u64>>,
+}
+
+impl JtiReplayCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `jti`, returns `false` if it was already recorded
+    pub fn insert(&self, jti: &str, expires_at: u64) -> bool {
+        let mut seen = self.seen.lock().expect("jti cache lock poisoned");
+        let now = now();
+
+        seen.retain(|_, expires_at| *expires_at > now);
+
+        if seen.contains_key(jti) {
+            return false;
+        }
+
+        seen.insert(jti.to_string(), expires_at);
+
+        true
+    }
+}
+
+/// Verifies a back-channel logout token with the tenant's JWKS and returns its
+/// claims, see https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation
+pub fn verify_logout_token(
+    params: LogoutTokenParameters,
+) -> Result<LogoutTokenClaims, LogoutTokenError> {
+    let claims = decode_with_jwks::<LogoutTokenClaims>(
+        params.logout_token,
+        params.client_id,
+        params.issuer,
+        params.jwks,
+    )?;
+
+    if !claims
+        .events
+        .get(BACK_CHANNEL_LOGOUT_EVENT)
+        .is_some_and(Value::is_object)
+    {
+        return Err(LogoutTokenError::MissingLogoutEvent);
+    }
+
+    if claims.sid.is_none() && claims.sub.is_none() {
+        return Err(LogoutTokenError::MissingSidAndSub);
+    }
+
+    if claims.nonce.is_some() {
+        return Err(LogoutTokenError::NonceNotAllowed);
+    }
+
+    if !params.replay_cache.insert(&claims.jti, claims.exp) {
+        return Err(LogoutTokenError::Replayed);
+    }
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use josekit::{
+        jwk::{Jwk, JwkSet},
+        jws::{alg::ecdsa::EcdsaJwsAlgorithm::Es384, JwsHeader},
+        jwt::JwtPayload,
+    };
+
+    use super::*;
+
+    fn sign_logout_token(claims: &Value) -> (String, jsonwebtoken::jwk::JwkSet) {
+        let key_pair = Es384
+            .generate_key_pair()
+            .expect("couldn't generate key pair");
+
+        let mut jwk_public: Jwk = key_pair.to_jwk_public_key();
+        jwk_public.set_key_id("123");
+        jwk_public.set_algorithm("ES384");
+
+        let token_signer = Es384
+            .signer_from_jwk(&key_pair.to_jwk_private_key())
+            .unwrap();
+
+        let mut header = JwsHeader::new();
+        header.set_key_id("123");
+        header.set_token_type("logout+jwt");
+
+        let payload = JwtPayload::from_map(claims.as_object().unwrap().clone()).unwrap();
+        let token = josekit::jwt::encode_with_signer(&payload, &header, &token_signer).unwrap();
+
+        let mut initial_map: josekit::Map<String, josekit::Value> = josekit::Map::new();
+        initial_map.insert(
+            "keys".to_string(),
+            josekit::Value::from(Vec::<String>::new()),
+        );
+
+        let mut set = JwkSet::from_map(initial_map).unwrap();
+        set.push_key(jwk_public);
+
+        (token, serde_json::from_str(&set.to_string()).unwrap())
+    }
+
+    fn claims() -> Value {
+        serde_json::json!({
+            "iss": "https://logto.dev/oidc",
+            "aud": "client_id_value",
+            "iat": now(),
+            "exp": now() + 120,
+            "jti": "jti_value",
+            "events": { BACK_CHANNEL_LOGOUT_EVENT: {} },
+            "sid": "sid_value",
+        })
+    }
+
+    fn verify(
+        token: &str,
+        jwks: &jsonwebtoken::jwk::JwkSet,
+        replay_cache: &JtiReplayCache,
+    ) -> Result<LogoutTokenClaims, LogoutTokenError> {
+        verify_logout_token(LogoutTokenParameters {
+            logout_token: token,
+            client_id: "client_id_value",
+            issuer: "https://logto.dev/oidc",
+            jwks,
+            replay_cache,
+        })
+    }
+
+    #[test]
+    fn verify_logout_token_works() {
+        let (token, jwks) = sign_logout_token(&claims());
+        let replay_cache = JtiReplayCache::new();
+
+        let claims = verify(&token, &jwks, &replay_cache).unwrap();
+        assert_eq!(claims.sid.as_deref(), Some("sid_value"));
+
+        assert!(matches!(
+            verify(&token, &jwks, &replay_cache),
+            Err(LogoutTokenError::Replayed)
+        ));
+    }
+
+    #[test]
+    fn fail_verify_logout_token_invalid_claims() {
+        let replay_cache = JtiReplayCache::new();
+
+        let mut with_nonce = claims();
+        with_nonce["nonce"] = "nonce_value".into();
+        let (token, jwks) = sign_logout_token(&with_nonce);
+        assert!(matches!(
+            verify(&token, &jwks, &replay_cache),
+            Err(LogoutTokenError::NonceNotAllowed)
+        ));
+
+        let mut without_event = claims();
+        without_event["events"] = serde_json::json!({});
+        let (token, jwks) = sign_logout_token(&without_event);
+        assert!(matches!(
+            verify(&token, &jwks, &replay_cache),
+            Err(LogoutTokenError::MissingLogoutEvent)
+        ));
+
+        let mut without_sid = claims();
+        without_sid.as_object_mut().unwrap().remove("sid");
+        let (token, jwks) = sign_logout_token(&without_sid);
+        assert!(matches!(
+            verify(&token, &jwks, &replay_cache),
+            Err(LogoutTokenError::MissingSidAndSub)
+        ));
+    }
+}